@@ -0,0 +1,146 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+/// Answers printed by a day as `label: value` lines, keyed by label.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Answers(BTreeMap<String, String>);
+
+impl Answers {
+    pub fn parse(output: &str) -> Self {
+        Self(
+            output
+                .lines()
+                .filter_map(|l| l.split_once(": "))
+                .map(|(k, v)| (k.trim().replace(' ', ""), v.trim().to_owned()))
+                .collect(),
+        )
+    }
+
+    /// Known answers live next to the input they belong to, e.g. `1.txt` -> `1.answers`.
+    pub fn path_for(input: &Path) -> PathBuf {
+        input.with_extension("answers")
+    }
+
+    pub fn load(input: &Path) -> Result<Option<Self>> {
+        let path = Self::path_for(input);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let answers =
+            read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))?;
+        Ok(Some(Self::parse(&answers)))
+    }
+
+    pub fn get(&self, label: &str) -> Option<&str> {
+        self.0.get(label).map(String::as_str)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    /// One entry per label in any of the answers, so parts that stopped printing show up too.
+    pub fn diff<'a>(&'a self, prev: Option<&'a Self>, known: Option<&'a Self>) -> Vec<Diff<'a>> {
+        let labels = [Some(self), prev, known]
+            .into_iter()
+            .flatten()
+            .flat_map(|a| a.0.keys().map(String::as_str))
+            .collect::<BTreeSet<_>>();
+        labels
+            .into_iter()
+            .map(|label| Diff {
+                label,
+                value: self.get(label),
+                prev: prev.and_then(|p| p.get(label)),
+                known: known.and_then(|k| k.get(label)),
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug)]
+pub struct Diff<'a> {
+    pub label: &'a str,
+    /// `None` when the part printed nothing this time.
+    pub value: Option<&'a str>,
+    pub prev: Option<&'a str>,
+    pub known: Option<&'a str>,
+}
+
+impl Display for Diff<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let Some(value) = self.value else {
+            write!(f, "{}: MISSING", self.label)?;
+            if let Some(known) = self.known {
+                write!(f, " [expected {known}]")?;
+            }
+            return match self.prev {
+                Some(prev) => write!(f, " (was {prev})"),
+                None => Ok(()),
+            };
+        };
+        write!(f, "{}: {value}", self.label)?;
+        match self.known {
+            Some(known) if known == value => write!(f, " [ok]")?,
+            Some(known) => write!(f, " [WRONG, expected {known}]")?,
+            None => {}
+        }
+        match self.prev {
+            Some(prev) if prev != value => write!(f, " (was {prev})"),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_diff() {
+        let cur = Answers::parse("part1: 42\npart 2: 7\nnoise\n");
+        assert_eq!(cur.get("part1"), Some("42"));
+        assert_eq!(cur.get("part2"), Some("7"));
+
+        let prev = Answers::parse("part1: 41\npart2: 7");
+        let known = Answers::parse("part1: 42\npart2: 8");
+        let diff = cur
+            .diff(Some(&prev), Some(&known))
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            diff,
+            ["part1: 42 [ok] (was 41)", "part2: 7 [WRONG, expected 8]"]
+        );
+    }
+
+    #[test]
+    fn missing_parts() {
+        // part 2 panicked this time
+        let cur = Answers::parse("part1: 42\n");
+        let prev = Answers::parse("part1: 42\npart2: 7");
+        let known = Answers::parse("part1: 42\npart2: 7\npart3: 1");
+        let diff = cur
+            .diff(Some(&prev), Some(&known))
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            diff,
+            [
+                "part1: 42 [ok]",
+                "part2: MISSING [expected 7] (was 7)",
+                "part3: MISSING [expected 1]"
+            ]
+        );
+        assert_eq!(
+            cur.diff(Some(&prev), None)[1].to_string(),
+            "part2: MISSING (was 7)"
+        );
+    }
+}
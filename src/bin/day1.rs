@@ -1,65 +1,67 @@
+use std::env;
+
 use aoc_2023_rust::aoc;
+use aoc_2023_rust::calibration::{Scanner, Summary, Vocabulary};
 
 fn main() {
     let aoc = aoc().unwrap();
-    let input = aoc.read_input(1).unwrap();
-    part1(&input);
-    part2(&input);
+    // `--lines` prints every line's value as well
+    let verbose = env::args().any(|a| a == "--lines");
+    for (part, vocabulary) in [
+        ("part1", Vocabulary::digits()),
+        ("part2", Vocabulary::english()),
+    ] {
+        let scanner = Scanner::new(&vocabulary);
+        let input = aoc.open_input(1).unwrap();
+        let summary = if verbose {
+            scanner.read(input, |line, value| match value {
+                Some(v) => println!("{part} line {line}: {v}"),
+                None => println!("{part} line {line}: -"),
+            })
+        } else {
+            scanner.par_read(input, 1 << 12)
+        }
+        .unwrap();
+        report(part, &summary);
+        println!("{part}: {}", summary.total);
+    }
 }
 
-fn part1(input: &str) {
-    let sum: u32 = input
-        .lines()
-        .map(|l| {
-            let mut digits = l.chars().filter(char::is_ascii_digit);
-            let first = digits.next().unwrap();
-            let last = digits.last().unwrap_or(first);
-            (first, last)
-        })
-        .map(|(a, b)| a.to_digit(10).unwrap() * 10 + b.to_digit(10).unwrap())
-        .sum();
-    println!("part1: {sum}")
+/// Lines without any digit are left out of the sum, but not silently.
+fn report(part: &str, summary: &Summary) {
+    for line in &summary.missing {
+        eprintln!("{part} line {line}: no digit");
+    }
 }
 
-fn part2(input: &str) {
-    let nums = [
-        "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
-    ];
-    let mut sum = 0;
-    for line in input.lines() {
-        let mut offset = 0;
-        let mut first = None;
-        'outer: while offset < line.len() {
-            let char = line.chars().nth(offset).unwrap();
-            if char.is_ascii_digit() {
-                first = Some(char.to_digit(10).unwrap());
-                break;
-            }
-            for (i, &num) in nums.iter().enumerate() {
-                if line[offset..].starts_with(num) {
-                    first = Some(i as u32 + 1); // 0-indexed
-                    break 'outer;
-                }
-            }
-            offset += 1;
-        }
-        let mut limit = line.len();
-        let mut last = None;
-        'outer: while limit > 0 {
-            let char = line.chars().nth(limit - 1).unwrap();
-            if char.is_ascii_digit() {
-                last = Some(char.to_digit(10).unwrap());
-                break;
-            }
-            for (i, &num) in nums.iter().enumerate() {
-                if line[..limit].ends_with(num) {
-                    last = Some(i as u32 + 1); // 0-indexed
-                    break 'outer;
-                }
-            }
-            limit -= 1;
-        }
-        sum += first.unwrap() * 10 + last.unwrap();
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solve(input: &str, vocabulary: Vocabulary) -> u64 {
+        let scanner = Scanner::new(&vocabulary);
+        scanner.read(input.as_bytes(), |_, _| ()).unwrap().total
+    }
+
+    #[test]
+    fn p1() {
+        let input = "1abc2
+pqr3stu8vwx
+a1b2c3d4e5f
+treb7uchet";
+        assert_eq!(solve(input, Vocabulary::digits()), 142);
+    }
+
+    #[test]
+    fn p2() {
+        let input = "two1nine
+eightwothree
+abcone2threexyz
+xtwone3four
+4nineeightseven2
+zoneight234
+7pqrstsixteen
+no digits at all";
+        assert_eq!(solve(input, Vocabulary::english()), 281);
     }
-    println!("part2: {sum}")
 }
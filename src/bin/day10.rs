@@ -1,153 +1,94 @@
-use itertools::Itertools;
-use num::abs;
+use std::ops::Deref;
 
-use aoc_2023_rust::aoc;
-use Direction::{Left, Right};
+use itertools::Itertools;
 
-use crate::Direction::{Down, Up};
+use aoc_2023_rust::geometry::Polygon;
+use aoc_2023_rust::grid::Direction::{Down, Left, Right, Up};
+use aoc_2023_rust::grid::{self, Direction, Grid, GridExt, Pos};
+use aoc_2023_rust::viz::{self, Frame, Style};
+use aoc_2023_rust::{aoc, search};
 
 fn main() {
     let aoc = aoc().unwrap();
     let input = aoc.read_input(10).unwrap();
     let map = Map::parse(&input);
+    if let Some(viz) = viz::Output::from_args().unwrap() {
+        viz.emit(&[frame(&map)]).unwrap();
+        return;
+    }
     println!("part1: {}", part1(&map));
     println!("part2: {}", part2(&map));
 }
 
 fn part1(map: &Map) -> usize {
-    let (size_x, size_y) = map.size();
-    let mut visited = vec![vec![false; size_x]; size_y];
-    let mut mv_cnt = 0usize;
-    let mut cur = vec![map.starting_pos()];
-    while !cur.is_empty() {
-        let mut next = vec![];
-        for pos in cur {
-            if visited[pos.x][pos.y] {
-                continue;
-            }
-            visited[pos.x][pos.y] = true;
-            let connects = map.connects_to(pos);
-            next.extend(connects);
-        }
-        cur = next
-            .into_iter()
-            .filter(|pos| !visited[pos.x][pos.y])
-            .collect_vec();
-        mv_cnt += 1;
-    }
-    mv_cnt - 1
+    search::bfs([map.starting_pos()], |&pos| map.connects_to(pos))
+        .iter()
+        .map(|(_, d)| d)
+        .max()
+        .unwrap()
 }
 
 fn part2(map: &Map) -> usize {
-    let (size_x, size_y) = map.size();
-    let mut visited = vec![vec![false; size_x]; size_y];
-    let start = map.starting_pos();
-    let mut stack = vec![start];
-    visited[start.x][start.y] = true;
-    let mut boundary = vec![start];
-    while let Some(pos) = stack.pop() {
-        for pos in map.connects_to(pos) {
-            if !visited[pos.x][pos.y] {
-                visited[pos.x][pos.y] = true;
-                stack.push(pos);
-                boundary.push(pos);
-            }
-        }
-    }
-    let b = boundary.len();
-    // Shoelace: A = sum((y1 + y2) * (x1 - x2)) / 2
-    let area = boundary
+    let vertices = main_loop(map)
         .into_iter()
-        .circular_tuple_windows()
-        .map(|(p1, p2)| (p1.y as i64 + p2.y as i64) * (p1.x as i64 - p2.x as i64))
-        .sum::<i64>()
-        / 2;
-    // Pick's theorem: A = i + b / 2 - 1 => i = A - b / 2 + 1
-    abs(area) as usize - b / 2 + 1
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
+        .map(|(r, c)| (r as i64, c as i64))
+        .collect_vec();
+    Polygon::from_vertices(vertices)
+        .unwrap()
+        .interior_points()
+        .unwrap() as usize
 }
 
-impl Direction {
-    fn opposite(&self) -> Self {
-        match self {
-            Up => Down,
-            Down => Up,
-            Left => Right,
-            Right => Left,
-        }
+/// The pipes of the loop through `S`, in order.
+fn main_loop(map: &Map) -> Vec<Pos> {
+    let start = map.starting_pos();
+    let mut boundary = vec![start];
+    let (mut prev, mut cur) = (start, map.connects_to(start)[0]);
+    while cur != start {
+        boundary.push(cur);
+        // nothing accepts a move onto `S`, so running out of pipe means we are back
+        let next = map
+            .connects_to(cur)
+            .into_iter()
+            .find(|&p| p != prev)
+            .unwrap_or(start);
+        (prev, cur) = (cur, next);
     }
+    boundary
 }
 
-#[derive(Debug, Clone, Copy)]
-struct Pos {
-    x: usize,
-    y: usize,
+fn frame(map: &Map) -> Frame {
+    let start = map.starting_pos();
+    Frame::chars(map)
+        .overlay(main_loop(map), Style::fg(viz::YELLOW))
+        .overlay([start], Style::bg(viz::RED))
 }
 
-struct Map(Vec<Vec<char>>); // [y][x]
+struct Map(Grid<char>);
 
 impl Map {
     fn parse(input: &str) -> Self {
-        Self(
-            input
-                .lines()
-                .map(|line| line.chars().collect_vec())
-                .collect_vec(),
-        )
-    }
-
-    fn size(&self) -> (usize, usize) {
-        (self.0[0].len(), self.0.len())
-    }
-
-    fn get(&self, pos: Pos) -> char {
-        self.0[pos.y][pos.x]
+        Self(grid::parse(input))
     }
 
     fn starting_pos(&self) -> Pos {
-        self.0
-            .iter()
-            .enumerate()
-            .find_map(|(y, line)| {
-                line.iter().enumerate().find_map(
-                    |(x, &c)| {
-                        if c == 'S' {
-                            Some(Pos { x, y })
-                        } else {
-                            None
-                        }
-                    },
-                )
-            })
-            .unwrap()
+        self.position(|&c| c == 'S').unwrap()
     }
 
     fn connects_to(&self, pos: Pos) -> Vec<Pos> {
-        [Up, Down, Left, Right]
+        Direction::ALL
             .into_iter()
-            .filter(|&d| self.get(pos).connects(d))
-            .filter_map(|d| self.nav(pos, d))
+            .filter(|&d| self[pos].connects(d))
+            .filter_map(|d| self.nav(pos, d).filter(|&p| self[p].accepts(d)))
             .collect_vec()
     }
+}
+
+impl Deref for Map {
+    type Target = Grid<char>;
 
-    fn nav(&self, pos: Pos, direction: Direction) -> Option<Pos> {
-        let (size_x, size_y) = self.size();
-        let (x, y) = (pos.x, pos.y);
-        let pos = match direction {
-            Up if y > 0 => Some(Pos { x, y: y - 1 }),
-            Down if y + 1 < size_y => Some(Pos { x, y: y + 1 }),
-            Left if x > 0 => Some(Pos { x: x - 1, y }),
-            Right if x + 1 < size_x => Some(Pos { x: x + 1, y }),
-            _ => None,
-        };
-        pos.filter(|&pos| self.get(pos).accepts(direction))
+    fn deref(&self) -> &Self::Target {
+        &self.0
     }
 }
 
@@ -206,4 +147,37 @@ LJ..."#
             4
         );
     }
+
+    #[test]
+    fn p2() {
+        assert_eq!(
+            part2(&Map::parse(
+                r#"...........
+.S-------7.
+.|F-----7|.
+.||.....||.
+.||.....||.
+.|L-7.F-J|.
+.|..|.|..|.
+.L--J.L--J.
+..........."#
+            )),
+            4
+        );
+        assert_eq!(
+            part2(&Map::parse(
+                r#"FF7FSF7F7F7F7F7F---7
+L|LJ||||||||||||F--J
+FL-7LJLJ||||||LJL-77
+F--JF--7||LJLJ7F7FJ-
+L---JF-JLJ.||-FJLJJ7
+|F|F-JF---7F7-L7L|7|
+|FFJF7L7F-JF7|JL---7
+7-L-JL7||F7|L7F-7F7|
+L.L7LFJ|||||FJL7||LJ
+L7JLJL-JLJLJL--JLJ.L"#
+            )),
+            10
+        );
+    }
 }
@@ -1,8 +1,7 @@
-use std::ops::Index;
-
 use itertools::Itertools;
 
 use aoc_2023_rust::aoc;
+use aoc_2023_rust::grid::{self, manhattan, Grid, Pos};
 
 fn main() {
     let aoc = aoc().unwrap();
@@ -12,103 +11,78 @@ fn main() {
 }
 
 fn solve(map: &Map) -> usize {
-    map.grid
-        .galaxies()
+    galaxies(&map.grid)
         .into_iter()
         .tuple_combinations()
-        .map(|(a, b)| map.distance(map.translate(a), map.translate(b)))
+        .map(|(a, b)| manhattan(map.translate(a), map.translate(b)))
         .sum()
 }
 
-struct Grid(Vec<Vec<char>>); // [row][col]
-
-impl Grid {
-    fn parse(input: &str) -> Self {
-        Self(
-            input
-                .lines()
-                .map(|line| line.chars().collect_vec())
-                .collect_vec(),
-        )
-    }
-
-    fn size(&self) -> (usize, usize) {
-        // x, y
-        (self.0[0].len(), self.0.len())
-    }
-
-    fn remap_rows(&self, expand_factor: usize) -> Vec<usize> {
-        let mut i = 0;
-        let mut remap = Vec::with_capacity(self.0.len());
-        for row in self.0.iter() {
-            remap.push(i);
-            if row.iter().all(|&c| c == '.') {
-                i += expand_factor;
-            }
-            i += 1;
+fn remap<'a>(
+    lines: impl Iterator<Item = impl Iterator<Item = &'a char>>,
+    expand_factor: usize,
+) -> Vec<usize> {
+    let mut i = 0;
+    let mut remap = vec![];
+    for mut line in lines {
+        remap.push(i);
+        if line.all(|&c| c == '.') {
+            i += expand_factor;
         }
-        remap
-    }
-
-    fn remap_cols(&self, expand_factor: usize) -> Vec<usize> {
-        let mut i = 0;
-        let mut remap = Vec::with_capacity(self.0[0].len());
-        for col in 0..self.0[0].len() {
-            remap.push(i);
-            if self.0.iter().all(|row| row[col] == '.') {
-                i += expand_factor;
-            }
-            i += 1;
-        }
-        remap
-    }
-
-    fn galaxies(&self) -> Vec<(usize, usize)> {
-        let mut galaxies = Vec::new();
-        for row in 0..self.0.len() {
-            for col in 0..self.0[0].len() {
-                if self[(col, row)] != '.' {
-                    galaxies.push((col, row));
-                }
-            }
-        }
-        galaxies
+        i += 1;
     }
+    remap
 }
 
-impl Index<(usize, usize)> for Grid {
-    type Output = char;
-
-    fn index(&self, pos: (usize, usize)) -> &Self::Output {
-        &self.0[pos.1][pos.0]
-    }
+fn galaxies(grid: &Grid<char>) -> Vec<Pos> {
+    grid.indexed_iter()
+        .filter(|(_, &c)| c != '.')
+        .map(|(pos, _)| pos)
+        .collect_vec()
 }
 
 struct Map {
-    grid: Grid,
-    x_remap: Vec<usize>,
-    y_remap: Vec<usize>,
+    grid: Grid<char>,
+    row_remap: Vec<usize>,
+    col_remap: Vec<usize>,
 }
 
 impl Map {
     fn parse(input: &str, expand_factor: usize) -> Self {
-        let grid = Grid::parse(input);
-        let x_remap = grid.remap_cols(expand_factor);
-        let y_remap = grid.remap_rows(expand_factor);
+        let grid = grid::parse(input);
+        let row_remap = remap(grid.iter_rows(), expand_factor);
+        let col_remap = remap(grid.iter_cols(), expand_factor);
         Self {
             grid,
-            x_remap,
-            y_remap,
+            row_remap,
+            col_remap,
         }
     }
 
-    fn translate(&self, pos: (usize, usize)) -> (usize, usize) {
-        (self.x_remap[pos.0], self.y_remap[pos.1])
+    fn translate(&self, pos: Pos) -> Pos {
+        (self.row_remap[pos.0], self.col_remap[pos.1])
     }
+}
 
-    fn distance(&self, a: (usize, usize), b: (usize, usize)) -> usize {
-        let (x1, y1) = a;
-        let (x2, y2) = b;
-        ((x1 as isize - x2 as isize).abs() + (y1 as isize - y2 as isize).abs()) as usize
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &str = "...#......
+.......#..
+#.........
+..........
+......#...
+.#........
+.........#
+..........
+.......#..
+#...#.....";
+
+    #[test]
+    fn test() {
+        assert_eq!(solve(&Map::parse(INPUT, 1)), 374);
+        assert_eq!(solve(&Map::parse(INPUT, 9)), 1030);
+        assert_eq!(solve(&Map::parse(INPUT, 99)), 8410);
     }
 }
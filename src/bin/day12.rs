@@ -38,7 +38,7 @@ fn part2(input: &str) {
 }
 
 fn expand(input: &str, counts: &[usize]) -> (String, Vec<usize>) {
-    (iter::repeat(input).take(5).join("?"), counts.repeat(5))
+    (iter::repeat_n(input, 5).join("?"), counts.repeat(5))
 }
 
 fn solve(input: &str, counts: &[usize]) -> usize {
@@ -54,9 +54,7 @@ fn solve(input: &str, counts: &[usize]) -> usize {
 fn gen_pattern(counts: &[usize]) -> Vec<char> {
     let mut state = vec!['.'];
     for count in counts {
-        for _ in 0..*count {
-            state.push('#');
-        }
+        state.extend(iter::repeat_n('#', *count));
         state.push('.');
     }
     state
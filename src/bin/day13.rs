@@ -2,11 +2,17 @@ use std::iter::zip;
 
 use itertools::Itertools;
 
-use aoc_2023_rust::aoc;
+use aoc_2023_rust::grid::Pos;
+use aoc_2023_rust::viz::{self, Cell, Frame, Style};
+use aoc_2023_rust::{aoc, grid, parse};
 
 fn main() {
     let aoc = aoc().unwrap();
     let input = aoc.read_input(13).unwrap();
+    if let Some(viz) = viz::Output::from_args().unwrap() {
+        viz.emit(&[frame(&parse(&input))]).unwrap();
+        return;
+    }
     part1(&input);
     part2(&input);
 }
@@ -25,15 +31,79 @@ fn part2(input: &str) {
     println!("part2: {}", sum);
 }
 
-fn solve(mut grid: Grid, tolerance: usize) -> usize {
-    (1..grid.rows())
-        .find(|&i| mirror_diff(&grid, i) == tolerance)
-        .map(|i| i * 100)
-        .or_else(|| {
-            grid.transpose();
-            (1..grid.rows()).find(|&i| mirror_diff(&grid, i) == tolerance)
-        })
-        .unwrap()
+fn solve(grid: Grid, tolerance: usize) -> usize {
+    match mirror(&grid, tolerance) {
+        Mirror::Row(i) => i * 100,
+        Mirror::Col(i) => i,
+    }
+}
+
+/// A mirror between rows (or columns) `i - 1` and `i`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Mirror {
+    Row(usize),
+    Col(usize),
+}
+
+fn mirror(grid: &Grid, tolerance: usize) -> Mirror {
+    if let Some(i) = (1..grid.rows()).find(|&i| mirror_diff(grid, i) == tolerance) {
+        return Mirror::Row(i);
+    }
+    let mut grid = grid.clone();
+    grid.transpose();
+    Mirror::Col(
+        (1..grid.rows())
+            .find(|&i| mirror_diff(&grid, i) == tolerance)
+            .unwrap(),
+    )
+}
+
+/// Every pattern stacked with a blank row between them, the rows or columns either side of the
+/// original mirror in blue and the smudge in red.
+fn frame(grids: &[Grid]) -> Frame {
+    let cols = grids.iter().map(Grid::cols).max().unwrap_or(0);
+    let mut stacked = grid::Grid::new(0, cols);
+    let (mut mirrors, mut smudges) = (vec![], vec![]);
+    for grid in grids {
+        let top = stacked.rows();
+        for r in 0..grid.rows() {
+            let mut row = grid.iter_row(r).copied().collect_vec();
+            row.resize(cols, ' ');
+            stacked.push_row(row);
+        }
+        stacked.push_row(vec![' '; cols]);
+        let cells = grid.indexed_iter().map(|(pos, _)| pos).collect_vec();
+        let near = |p: Pos, m: Mirror| match m {
+            Mirror::Row(i) => p.0 + 1 == i || p.0 == i,
+            Mirror::Col(i) => p.1 + 1 == i || p.1 == i,
+        };
+        let original = mirror(grid, 0);
+        mirrors.extend(
+            cells
+                .iter()
+                .copied()
+                .filter(|&p| near(p, original))
+                .map(|(r, c)| (top + r, c)),
+        );
+        let fixed = mirror(grid, 1);
+        let reflect = |(r, c): Pos| match fixed {
+            Mirror::Row(i) => (2 * i - 1).checked_sub(r).map(|r| (r, c)),
+            Mirror::Col(i) => (2 * i - 1).checked_sub(c).map(|c| (r, c)),
+        };
+        smudges.extend(
+            cells
+                .into_iter()
+                .filter(|&p| {
+                    reflect(p)
+                        .and_then(|(r, c)| grid.get(r, c))
+                        .is_some_and(|&q| q != grid[p])
+                })
+                .map(|(r, c)| (top + r, c)),
+        );
+    }
+    Frame::new(&stacked, |&c| Cell::plain(c))
+        .overlay(mirrors, Style::bg(viz::BLUE))
+        .overlay(smudges, Style::bg(viz::RED))
 }
 
 fn mirror_diff(grid: &Grid, i: usize) -> usize {
@@ -51,19 +121,9 @@ fn mirror_diff(grid: &Grid, i: usize) -> usize {
 }
 
 fn parse(input: &str) -> Vec<Grid> {
-    let mut grids = vec![];
-    let mut lines = input.lines();
-    loop {
-        let grid = (&mut lines)
-            .take_while(|l| !l.is_empty())
-            .map(|l| l.chars().collect_vec())
-            .collect_vec();
-        if grid.is_empty() {
-            break;
-        }
-        grids.push(grid.into());
-    }
-    grids
+    parse::blocks(input)
+        .map(|block| grid::parse(&block))
+        .collect_vec()
 }
 
 #[cfg(test)]
@@ -100,4 +160,25 @@ mod tests {
         assert_eq!(solve(grids[0].clone(), 1), 300);
         assert_eq!(solve(grids[1].clone(), 1), 100);
     }
+
+    #[test]
+    fn mirrors() {
+        let grids = parse(INPUT);
+        assert_eq!(mirror(&grids[0], 0), Mirror::Col(5));
+        assert_eq!(mirror(&grids[0], 1), Mirror::Row(3));
+        let ansi = frame(&grids).ansi();
+        // both patterns, a blank row after each
+        assert_eq!(ansi.lines().count(), 7 + 1 + 7 + 1);
+        // the smudge of the first pattern is its top left corner, mirrored on row 4
+        let red = "\x1b[48;2;220;50;47m";
+        assert!(ansi
+            .lines()
+            .next()
+            .unwrap()
+            .split("\x1b[0m")
+            .next()
+            .unwrap()
+            .contains(red));
+        assert_eq!(ansi.matches(red).count(), 4);
+    }
 }
@@ -1,13 +1,14 @@
-use std::collections::HashMap;
-
-use itertools::Itertools;
-
-use aoc_2023_rust::aoc;
+use aoc_2023_rust::viz::{self, Cell, Frame};
+use aoc_2023_rust::{aoc, cycle, grid};
 
 fn main() {
     let aoc = aoc().unwrap();
     let input = aoc.read_input(14).unwrap();
     let grid = parse_grid(&input);
+    if let Some(viz) = viz::Output::from_args().unwrap() {
+        viz.emit(&spin_frames(grid, 20)).unwrap();
+        return;
+    }
     println!("part1: {}", part1(grid.clone()));
     println!("part2: {}", part2(grid.clone()));
 }
@@ -15,11 +16,7 @@ fn main() {
 type Grid = grid::Grid<char>;
 
 fn parse_grid(input: &str) -> Grid {
-    input
-        .lines()
-        .map(|l| l.chars().collect_vec())
-        .collect_vec()
-        .into()
+    grid::parse(input)
 }
 
 fn part1(mut grid: Grid) -> usize {
@@ -27,23 +24,33 @@ fn part1(mut grid: Grid) -> usize {
     weight(&grid)
 }
 
-fn part2(mut grid: Grid) -> usize {
-    let mut seen = HashMap::new();
-    let count = 1_000_000_000;
-    for i in 0..count {
-        if let Some(j) = seen.get(&grid) {
-            let cycle = i - j;
-            let remaining = count - i;
-            let remaining = remaining % cycle;
-            for _ in 0..remaining {
-                tick4(&mut grid);
-            }
-            break;
-        }
-        seen.insert(grid.clone(), i);
+fn part2(grid: Grid) -> usize {
+    let grid = cycle::nth(grid, 1_000_000_000, |g| {
+        let mut g = g.clone();
+        tick4(&mut g);
+        g
+    });
+    weight(&grid)
+}
+
+/// The platform before and after each of the first `cycles` spin cycles.
+fn spin_frames(mut grid: Grid, cycles: usize) -> Vec<Frame> {
+    let mut frames = vec![frame(&grid)];
+    for _ in 0..cycles {
         tick4(&mut grid);
+        frames.push(frame(&grid));
     }
-    weight(&grid)
+    frames
+}
+
+fn frame(grid: &Grid) -> Frame {
+    Frame::new(grid, |&c| match c {
+        'O' => Cell {
+            fg: viz::YELLOW,
+            ..Cell::plain(c)
+        },
+        _ => Cell::plain(c),
+    })
 }
 
 fn tick4(grid: &mut Grid) {
@@ -1,23 +1,23 @@
-use grid::Grid;
 use itertools::{chain, Itertools};
 
 use aoc_2023_rust::aoc;
+use aoc_2023_rust::grid::{self, Direction, Grid, GridExt, Pos};
+use aoc_2023_rust::viz::{self, Frame, Style};
 
 fn main() {
     let aoc = aoc().unwrap();
     let input = aoc.read_input(16).unwrap();
     let grid = parse_grid(&input);
+    if let Some(viz) = viz::Output::from_args().unwrap() {
+        viz.emit(&[frame(&grid)]).unwrap();
+        return;
+    }
     println!("part1: {}", part1(&grid));
     println!("part2: {}", part2(&grid));
 }
 
 fn parse_grid(input: &str) -> Grid<char> {
-    input
-        .trim()
-        .lines()
-        .map(|l| l.trim().chars().collect_vec())
-        .collect_vec()
-        .into()
+    grid::parse(input)
 }
 
 fn part1(grid: &Grid<char>) -> usize {
@@ -36,36 +36,11 @@ fn part2(grid: &Grid<char>) -> usize {
     .unwrap()
 }
 
-type Pos = (usize, usize);
-
-#[derive(Debug, Copy, Clone)]
-enum Direction {
-    Up = 0,
-    Down,
-    Left,
-    Right,
-}
-
-impl Direction {
-    fn rotate_right(self) -> Self {
-        match self {
-            Self::Up => Self::Right,
-            Self::Down => Self::Left,
-            Self::Left => Self::Down,
-            Self::Right => Self::Up,
-        }
-    }
-    fn rotate_left(self) -> Self {
-        match self {
-            Self::Up => Self::Left,
-            Self::Down => Self::Right,
-            Self::Left => Self::Up,
-            Self::Right => Self::Down,
-        }
-    }
+fn solve(grid: &Grid<char>, pos: Pos, dir: Direction) -> usize {
+    energized(grid, pos, dir).len()
 }
 
-fn solve(grid: &Grid<char>, pos: Pos, dir: Direction) -> usize {
+fn energized(grid: &Grid<char>, pos: Pos, dir: Direction) -> Vec<Pos> {
     let mut visited = Grid::init(grid.rows(), grid.cols(), [false; 4]);
     let mut beams = vec![(pos, dir)];
     while let Some((pos, dir)) = beams.pop() {
@@ -77,57 +52,38 @@ fn solve(grid: &Grid<char>, pos: Pos, dir: Direction) -> usize {
             beams.push(next);
         }
     }
-    visited.iter().filter(|&a| a.iter().any(|&b| b)).count()
+    visited
+        .indexed_iter()
+        .filter(|(_, a)| a.iter().any(|&b| b))
+        .map(|(pos, _)| pos)
+        .collect_vec()
+}
+
+fn frame(grid: &Grid<char>) -> Frame {
+    let tiles = energized(grid, (0, 0), Direction::Right);
+    Frame::chars(grid).overlay(tiles, Style::bg(viz::MAGENTA))
 }
 
 fn tick(grid: &Grid<char>, pos: Pos, dir: Direction) -> Vec<(Pos, Direction)> {
     let c = grid[pos];
-    match (c, dir) {
-        ('|', Direction::Left | Direction::Right) => [Direction::Up, Direction::Down]
-            .into_iter()
-            .filter_map(|d| nav(grid, pos, d))
-            .collect_vec(),
-        ('-', Direction::Up | Direction::Down) => [Direction::Left, Direction::Right]
-            .into_iter()
-            .filter_map(|d| nav(grid, pos, d))
-            .collect_vec(),
-        ('/', _) => nav(grid, pos, dir.rotate_right()).into_iter().collect_vec(),
-        ('\\', _) => nav(grid, pos, dir.rotate_left()).into_iter().collect_vec(),
-        _ => nav(grid, pos, dir).into_iter().collect_vec(),
-    }
+    let dirs = match (c, dir) {
+        ('|', Direction::Left | Direction::Right) => vec![Direction::Up, Direction::Down],
+        ('-', Direction::Up | Direction::Down) => vec![Direction::Left, Direction::Right],
+        ('/' | '\\', _) => vec![reflect(c, dir)],
+        _ => vec![dir],
+    };
+    dirs.into_iter()
+        .filter_map(|d| grid.nav(pos, d).map(|p| (p, d)))
+        .collect_vec()
 }
 
-fn nav(grid: &Grid<char>, pos: Pos, dir: Direction) -> Option<(Pos, Direction)> {
-    let (y, x) = pos;
-    match dir {
-        Direction::Up => {
-            if y == 0 {
-                None
-            } else {
-                Some(((y - 1, x), dir))
-            }
-        }
-        Direction::Down => {
-            if y == grid.rows() - 1 {
-                None
-            } else {
-                Some(((y + 1, x), dir))
-            }
-        }
-        Direction::Left => {
-            if x == 0 {
-                None
-            } else {
-                Some(((y, x - 1), dir))
-            }
-        }
-        Direction::Right => {
-            if x == grid.cols() - 1 {
-                None
-            } else {
-                Some(((y, x + 1), dir))
-            }
-        }
+fn reflect(mirror: char, dir: Direction) -> Direction {
+    match (mirror, dir) {
+        ('/', Direction::Up | Direction::Down) => dir.rotate_right(),
+        ('/', _) => dir.rotate_left(),
+        ('\\', Direction::Up | Direction::Down) => dir.rotate_left(),
+        ('\\', _) => dir.rotate_right(),
+        _ => unreachable!("invalid mirror: {}", mirror),
     }
 }
 
@@ -151,4 +107,10 @@ mod tests {
         let grid = parse_grid(INPUT);
         assert_eq!(part1(&grid), 46)
     }
+
+    #[test]
+    fn p2() {
+        let grid = parse_grid(INPUT);
+        assert_eq!(part2(&grid), 51)
+    }
 }
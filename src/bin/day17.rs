@@ -1,16 +1,18 @@
-use std::cmp::Reverse;
-use std::collections::BinaryHeap;
 use std::ops::RangeInclusive;
 
-use grid::Grid;
-use itertools::Itertools;
-
-use aoc_2023_rust::aoc;
+use aoc_2023_rust::grid::{self, Direction, Grid, GridExt, Pos};
+use aoc_2023_rust::viz::{self, Cell, Frame, Style};
+use aoc_2023_rust::{aoc, search};
 
 fn main() {
     let aoc = aoc().unwrap();
     let input = aoc.read_input(17).unwrap();
     let grid = parse_map(&input);
+    if let Some(viz) = viz::Output::from_args().unwrap() {
+        viz.emit(&[frame(&grid, 1..=3), frame(&grid, 4..=10)])
+            .unwrap();
+        return;
+    }
     println!("part1: {}", part1(&grid));
     println!("part2: {}", part2(&grid));
 }
@@ -18,15 +20,7 @@ fn main() {
 type Map = Grid<u8>;
 
 fn parse_map(input: &str) -> Map {
-    input
-        .lines()
-        .map(|l| {
-            l.chars()
-                .map(|c| c.to_digit(10).unwrap() as u8)
-                .collect_vec()
-        })
-        .collect_vec()
-        .into()
+    grid::parse_with(input, |c| c.to_digit(10).unwrap() as u8)
 }
 
 fn part1(grid: &Map) -> u32 {
@@ -38,97 +32,47 @@ fn part2(grid: &Map) -> u32 {
 }
 
 fn solve(grid: &Map, steps: RangeInclusive<usize>) -> u32 {
-    let mut visited = Grid::init(grid.rows(), grid.cols(), [false; 4]);
-    let mut next = BinaryHeap::new();
-    next.push((Reverse(0u32), (0usize, 0usize), Direction::Right));
-    next.push((Reverse(0u32), (0usize, 0usize), Direction::Down));
-    while let Some((Reverse(cost), pos, dir)) = next.pop() {
-        if visited[pos][dir as usize] {
-            continue;
-        }
-        visited[pos][dir as usize] = true;
-        if pos == (grid.rows() - 1, grid.cols() - 1) {
-            return cost;
-        }
+    best_path(grid, steps).0
+}
+
+/// The least heat loss and every block passed on the way.
+fn best_path(grid: &Map, steps: RangeInclusive<usize>) -> (u32, Vec<Pos>) {
+    let end = (grid.rows() - 1, grid.cols() - 1);
+    let starts = [((0, 0), Direction::Right), ((0, 0), Direction::Down)];
+    let successors = |&(pos, dir): &(Pos, Direction)| {
+        let mut next = vec![];
         for d in [dir.rotate_left(), dir.rotate_right()] {
             let mut pos = pos;
-            let mut cost = cost;
+            let mut cost = 0;
             for step in 1..=*steps.end() {
-                pos = match nav(grid, pos, d) {
+                pos = match grid.nav(pos, d) {
                     None => break,
                     Some(p) => p,
                 };
                 cost += grid[pos] as u32;
                 if step >= *steps.start() {
-                    next.push((Reverse(cost), pos, d));
+                    next.push(((pos, d), cost));
                 }
             }
         }
-    }
-    unreachable!()
-}
-
-type Pos = (usize, usize);
-
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
-enum Direction {
-    Up = 0,
-    Down,
-    Left,
-    Right,
-}
-
-impl Direction {
-    fn rotate_right(self) -> Self {
-        match self {
-            Self::Up => Self::Right,
-            Self::Down => Self::Left,
-            Self::Left => Self::Up,
-            Self::Right => Self::Down,
-        }
-    }
-    fn rotate_left(self) -> Self {
-        match self {
-            Self::Up => Self::Left,
-            Self::Down => Self::Right,
-            Self::Left => Self::Down,
-            Self::Right => Self::Up,
+        next
+    };
+    let (cost, states) = search::dijkstra_to(starts, successors, |&(pos, _)| pos == end).unwrap();
+    let mut path = vec![(0, 0)];
+    for &(to, d) in &states[1..] {
+        while *path.last().unwrap() != to {
+            path.push(grid.nav(*path.last().unwrap(), d).unwrap());
         }
     }
+    (cost, path)
 }
 
-fn nav<T>(grid: &Grid<T>, pos: Pos, dir: Direction) -> Option<Pos> {
-    let (y, x) = pos;
-    match dir {
-        Direction::Up => {
-            if y == 0 {
-                None
-            } else {
-                Some((y - 1, x))
-            }
-        }
-        Direction::Down => {
-            if y == grid.rows() - 1 {
-                None
-            } else {
-                Some((y + 1, x))
-            }
-        }
-        Direction::Left => {
-            if x == 0 {
-                None
-            } else {
-                Some((y, x - 1))
-            }
-        }
-        Direction::Right => {
-            if x == grid.cols() - 1 {
-                None
-            } else {
-                Some((y, x + 1))
-            }
-        }
-    }
+fn frame(grid: &Map, steps: RangeInclusive<usize>) -> Frame {
+    let (_, path) = best_path(grid, steps);
+    Frame::new(grid, |&h| {
+        Cell::plain(char::from_digit(h as u32, 10).unwrap())
+    })
+    .overlay(path, Style::bg(viz::RED))
 }
 
 #[cfg(test)]
@@ -153,5 +97,14 @@ mod tests {
     fn p1() {
         let grid = parse_map(INPUT);
         assert_eq!(part1(&grid), 102);
+        let (cost, path) = best_path(&grid, 1..=3);
+        assert_eq!(path.last(), Some(&(12, 12)));
+        assert_eq!(path[1..].iter().map(|&p| grid[p] as u32).sum::<u32>(), cost);
+    }
+
+    #[test]
+    fn p2() {
+        let grid = parse_map(INPUT);
+        assert_eq!(part2(&grid), 94);
     }
 }
@@ -1,7 +1,8 @@
 use itertools::Itertools;
-use num::abs;
 
 use aoc_2023_rust::aoc;
+use aoc_2023_rust::geometry::Polygon;
+use aoc_2023_rust::grid::Direction;
 
 fn main() {
     let aoc = aoc().unwrap();
@@ -33,28 +34,20 @@ fn parse_plan2(input: &str) -> Vec<(char, i64)> {
         .collect_vec()
 }
 
-fn solve(steps: &[(char, i64)]) -> i64 {
-    let mut y = 0;
-    let mut a = 1;
-    let mut b = 0;
-    for (d, n) in steps {
-        // shoelace: A = sum((y1 + y2) * (x1 - x2)) / 2
-        // since we only move on one axis at a time, -dx = (x2 - x1) is either 0 or -n,
-        // further simplifies to A = sum(y * -dx)
-        let (dy, dx) = dv(*d);
-        y += dy * n;
-        a += dx * -n * y;
-        b += n;
-    }
-    abs(a) + b / 2
+fn solve(steps: &[(char, i64)]) -> i128 {
+    let steps = steps.iter().map(|&(d, n)| (dir(d), n));
+    Polygon::from_steps((0, 0), steps)
+        .unwrap()
+        .dug_volume()
+        .unwrap()
 }
 
-fn dv(d: char) -> (i64, i64) {
+fn dir(d: char) -> Direction {
     match d {
-        'R' | '0' => (0, 1),
-        'D' | '1' => (1, 0),
-        'L' | '2' => (0, -1),
-        'U' | '3' => (-1, 0),
+        'R' | '0' => Direction::Right,
+        'D' | '1' => Direction::Down,
+        'L' | '2' => Direction::Left,
+        'U' | '3' => Direction::Up,
         _ => unreachable!("invalid direction: {}", d),
     }
 }
@@ -1,11 +1,10 @@
 use std::collections::HashMap;
 use std::ops::Index;
-use std::sync::Arc;
 
 use itertools::Itertools;
-use rayon::prelude::*;
 
 use aoc_2023_rust::aoc;
+use aoc_2023_rust::interval::IntervalSet;
 
 fn main() {
     let aoc = aoc().unwrap();
@@ -36,29 +35,7 @@ fn part1(sys: &System, parts: &[Part]) -> usize {
 }
 
 fn part2(sys: &System) -> usize {
-    let [x, m, a, s] = sys
-        .splits()
-        .map(|v| v.into_iter().tuple_windows::<(_, _)>().collect_vec())
-        .map(Arc::new);
-    x.par_iter()
-        .map(move |&x| {
-            let a = Arc::clone(&a);
-            let s = Arc::clone(&s);
-            m.iter()
-                .map(move |&m| {
-                    let s = Arc::clone(&s);
-                    a.iter()
-                        .map(move |&a| {
-                            let s = Arc::clone(&s);
-                            s.iter()
-                                .map(move |&s| sys.accepts_range([x, m, a, s]))
-                                .sum::<usize>()
-                        })
-                        .sum::<usize>()
-                })
-                .sum::<usize>()
-        })
-        .sum()
+    sys.count_accepted("in", [(); 4].map(|_| IntervalSet::from(1..4001)))
 }
 
 struct System(HashMap<String, Workflow>);
@@ -89,32 +66,28 @@ impl System {
         }
     }
 
-    fn accepts_range(&self, range: [(usize, usize); 4]) -> usize {
-        let part = Part(range.map(|(l, _)| l));
-        if self.accepts(&part) {
-            range.iter().map(|(l, r)| r - l).product()
-        } else {
-            0
-        }
-    }
-
-    fn conditions(&self) -> impl Iterator<Item = &Condition> {
-        self.0
-            .values()
-            .flat_map(|w| w.rules.iter().filter_map(|r| r.condition.as_ref()))
-    }
-
-    fn splits(&self) -> [Vec<usize>; 4] {
-        let mut splits = [vec![1, 4001], vec![1, 4001], vec![1, 4001], vec![1, 4001]];
-        for cond in self.conditions() {
-            let v = match cond.operator {
-                Operator::GreaterThan => cond.value + 1,
-                Operator::LessThan => cond.value,
-            };
-            splits[cond.source as usize].push(v);
+    fn count_accepted(&self, workflow: &str, mut ranges: [IntervalSet<usize>; 4]) -> usize {
+        let mut sum = 0;
+        for rule in &self.0[workflow].rules {
+            let mut matched = ranges.clone();
+            if let Some(cond) = &rule.condition {
+                let src = cond.source as usize;
+                let pass = ranges[src].intersection(&cond.accepted());
+                ranges[src] = ranges[src].difference(&pass);
+                matched[src] = pass;
+            }
+            if matched.iter().all(|r| !r.is_empty()) {
+                sum += match &rule.destination {
+                    Destination::Workflow(name) => self.count_accepted(name, matched),
+                    Destination::Accept => matched.iter().map(|r| r.measure()).product(),
+                    Destination::Reject => 0,
+                };
+            }
+            if rule.condition.is_none() {
+                break;
+            }
         }
-        splits.iter_mut().for_each(|v| v.sort_unstable());
-        splits
+        sum
     }
 }
 
@@ -177,6 +150,13 @@ impl Condition {
     fn eval(&self, p: &Part) -> bool {
         self.operator.eval(p[self.source], self.value)
     }
+
+    fn accepted(&self) -> IntervalSet<usize> {
+        match self.operator {
+            Operator::GreaterThan => IntervalSet::from(self.value + 1..usize::MAX),
+            Operator::LessThan => IntervalSet::from(0..self.value),
+        }
+    }
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -1,57 +1,561 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt::{Display, Formatter};
+use std::fs::read_to_string;
+
+use itertools::Itertools;
+
 use aoc_2023_rust::aoc;
+use aoc_2023_rust::parse;
+
+/// The bag from the puzzle statement, used unless `--bag` or a `2.bag` file next to the input
+/// says otherwise. Both take the same syntax as a draw.
+const PUZZLE_BAG: &str = "12 red, 13 green, 14 blue";
 
 fn main() {
     let aoc = aoc().unwrap();
     let input = aoc.read_input(2).unwrap();
-    part1(&input);
-    part2(&input);
-}
-
-fn part1(input: &str) {
-    let mut sum = 0u32;
-    'game: for line in input.lines() {
-        let (header, sets) = line.split_once(": ").unwrap();
-        let gid: u32 = header["Game ".len()..].parse().unwrap();
-        for set in sets.split("; ") {
-            for cubes in set.split(", ") {
-                let (count, color) = cubes.split_once(' ').unwrap();
-                let count: u32 = count.parse().unwrap();
-                let valid = match color {
-                    "red" if count <= 12 => true,
-                    "green" if count <= 13 => true,
-                    "blue" if count <= 14 => true,
-                    _ => false,
-                };
-                if !valid {
-                    continue 'game;
-                }
+    let games = parse_games(&input).unwrap();
+    let config = aoc.input_path(2).with_extension("bag");
+    let bag = match arg("--bag") {
+        Some(spec) => spec,
+        None if config.exists() => read_to_string(config).unwrap(),
+        None => PUZZLE_BAG.to_owned(),
+    };
+    let bag = Bag::parse(&bag).unwrap();
+    // `--stats` prints a table, `--stats=json` the same as JSON
+    let format = env::args().find_map(|a| match a.as_str() {
+        "--stats" => Some("table".to_owned()),
+        _ => a.strip_prefix("--stats=").map(str::to_owned),
+    });
+    if let Some(format) = format {
+        let cap = arg("--cap").map_or(DEFAULT_CAP, |c| c.parse().unwrap());
+        let stats = Stats::new(&games, &bag, cap);
+        match format.as_str() {
+            "table" => print!("{}", stats.table()),
+            "json" => println!("{}", stats.json()),
+            _ => panic!("unknown --stats format {format:?}, expected table or json"),
+        }
+        return;
+    }
+    println!("part1: {}", part1(&games, &bag));
+    println!("part2: {}", part2(&games, &bag));
+    if let Some(k) = arg("--smallest-bag") {
+        match smallest_bag(&games, k.parse().unwrap()) {
+            Some(bag) => println!("smallest bag for {k} games: {bag}"),
+            None => println!("there are fewer than {k} games"),
+        }
+    }
+}
+
+/// The value following `name` on the command line.
+fn arg(name: &str) -> Option<String> {
+    env::args().skip_while(|a| a != name).nth(1)
+}
+
+fn part1(games: &[Game], bag: &Bag) -> u32 {
+    possible(games, bag).map(|g| g.id).sum()
+}
+
+fn part2(games: &[Game], bag: &Bag) -> u64 {
+    games.iter().map(|g| g.min_bag().power(bag)).sum()
+}
+
+fn parse_games(input: &str) -> parse::Result<Vec<Game>> {
+    input.lines().map(Game::parse).collect()
+}
+
+/// Cube counts by colour; a colour that isn't listed counts as zero.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Ord, PartialOrd)]
+struct Bag(BTreeMap<String, u32>);
+
+/// A handful of cubes shown from the bag, in the same shape as a bag.
+type Draw = Bag;
+
+impl Bag {
+    /// `"3 blue, 4 red"`
+    fn parse(s: &str) -> parse::Result<Self> {
+        let mut bag = Self::default();
+        for cubes in s.split(',') {
+            let (count, colour): (u32, String) = parse::fields(cubes)?;
+            *bag.0.entry(colour).or_default() += count;
+        }
+        Ok(bag)
+    }
+
+    fn get(&self, colour: &str) -> u32 {
+        self.0.get(colour).copied().unwrap_or(0)
+    }
+
+    fn contains(&self, draw: &Draw) -> bool {
+        draw.0.iter().all(|(colour, &n)| n <= self.get(colour))
+    }
+
+    /// The smallest bag containing both.
+    fn union(mut self, other: &Self) -> Self {
+        for (colour, &n) in &other.0 {
+            let max = self.0.entry(colour.clone()).or_default();
+            *max = (*max).max(n);
+        }
+        self
+    }
+
+    fn total(&self) -> u32 {
+        self.0.values().sum()
+    }
+
+    /// The product over every colour in either bag, so a colour of `colours` that this bag
+    /// lacks makes it zero.
+    fn power(&self, colours: &Bag) -> u64 {
+        let mut names = self.0.keys().chain(colours.0.keys()).collect_vec();
+        names.sort_unstable();
+        names.dedup();
+        names.into_iter().map(|c| self.get(c) as u64).product()
+    }
+}
+
+impl Display for Bag {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let cubes = self.0.iter().map(|(colour, n)| format!("{n} {colour}"));
+        write!(f, "{}", cubes.format(", "))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Game {
+    id: u32,
+    draws: Vec<Draw>,
+}
+
+impl Game {
+    /// `"Game 1: 3 blue, 4 red; 1 red, 2 green"`
+    fn parse(line: &str) -> parse::Result<Self> {
+        let (id, draws): (u32, String) = parse::scan(line, "Game {}: {}")?;
+        let draws = draws.split(';').map(Bag::parse).try_collect()?;
+        Ok(Self { id, draws })
+    }
+
+    fn possible_with(&self, bag: &Bag) -> bool {
+        self.draws.iter().all(|d| bag.contains(d))
+    }
+
+    fn min_bag(&self) -> Bag {
+        self.draws.iter().fold(Bag::default(), Bag::union)
+    }
+}
+
+fn possible<'a>(games: &'a [Game], bag: &'a Bag) -> impl Iterator<Item = &'a Game> + 'a {
+    games.iter().filter(move |g| g.possible_with(bag))
+}
+
+/// The bag with the fewest cubes that makes at least `k` games possible, `None` if there aren't
+/// `k` games. Every colour but the last is tried at each level some game needs, so the work
+/// grows with the number of games to the power of the number of colours minus one.
+fn smallest_bag(games: &[Game], k: usize) -> Option<Bag> {
+    if k > games.len() {
+        return None;
+    }
+    if k == 0 {
+        return Some(Bag::default());
+    }
+    let needs = games.iter().map(Game::min_bag).collect_vec();
+    let colours = needs
+        .iter()
+        .flat_map(|b| b.0.keys().cloned())
+        .unique()
+        .sorted()
+        .collect_vec();
+    let Some((last, rest)) = colours.split_last() else {
+        return Some(Bag::default());
+    };
+    let mut best: Option<Bag> = None;
+    let levels = if rest.is_empty() {
+        vec![vec![]]
+    } else {
+        rest.iter()
+            .map(|c| {
+                needs
+                    .iter()
+                    .map(|b| b.get(c))
+                    .unique()
+                    .sorted()
+                    .collect_vec()
+            })
+            .multi_cartesian_product()
+            .collect_vec()
+    };
+    for limits in levels {
+        let mut bag = Bag(rest.iter().cloned().zip(limits).collect());
+        let mut last_needs = needs
+            .iter()
+            .filter(|b| rest.iter().all(|c| b.get(c) <= bag.get(c)))
+            .map(|b| b.get(last))
+            .collect_vec();
+        if last_needs.len() < k {
+            continue;
+        }
+        last_needs.sort_unstable();
+        bag.0.insert(last.clone(), last_needs[k - 1]);
+        if best
+            .as_ref()
+            .is_none_or(|b| (bag.total(), &bag) < (b.total(), b))
+        {
+            best = Some(bag);
+        }
+    }
+    best
+}
+
+/// The most cubes of one colour a candidate bag may hold when estimating, unless `--cap` says
+/// otherwise.
+const DEFAULT_CAP: u32 = 20;
+
+/// `ln(n!)` for every `n` up to some limit.
+struct LnFactorials(Vec<f64>);
+
+impl LnFactorials {
+    fn new(max: u32) -> Self {
+        let mut table = vec![0.0];
+        for n in 1..=max {
+            table.push(table[n as usize - 1] + (n as f64).ln());
+        }
+        Self(table)
+    }
+
+    fn choose(&self, n: u32, k: u32) -> f64 {
+        let t = &self.0;
+        t[n as usize] - t[k as usize] - t[(n - k) as usize]
+    }
+
+    /// Log-likelihood of pulling exactly `draw` as one handful from `bag`, without putting cubes
+    /// back until the handful is shown.
+    fn draw(&self, bag: &Bag, draw: &Draw) -> f64 {
+        if !bag.contains(draw) {
+            return f64::NEG_INFINITY;
+        }
+        let ways = draw
+            .0
+            .iter()
+            .map(|(colour, &n)| self.choose(bag.get(colour), n))
+            .sum::<f64>();
+        ways - self.choose(bag.total(), draw.total())
+    }
+
+    fn game(&self, bag: &Bag, game: &Game) -> f64 {
+        game.draws.iter().map(|d| self.draw(bag, d)).sum()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct GameStats {
+    id: u32,
+    draws: usize,
+    min_bag: Bag,
+    /// The bag of at most `cap` cubes per colour under which the draws were likeliest.
+    likeliest: Bag,
+    log_likelihood: f64,
+}
+
+#[derive(Debug, Clone)]
+struct Stats {
+    games: Vec<GameStats>,
+    /// The configured bag, then every other bag some game was likeliest under.
+    candidates: Vec<Bag>,
+    /// For each game, how many of its draws each candidate could have produced.
+    consistent: Vec<Vec<usize>>,
+    /// For each colour, how many draws showed each count of it.
+    colours: BTreeMap<String, BTreeMap<u32, usize>>,
+    /// `(game, draw, tightness)` for the draws coming closest to (or over) the configured bag,
+    /// tightness being the largest share of a colour's limit the draw uses.
+    constraining: Vec<(u32, usize, f64)>,
+}
+
+impl Stats {
+    fn new(games: &[Game], bag: &Bag, cap: u32) -> Self {
+        let max_total = games
+            .iter()
+            .map(|g| g.min_bag().0.values().map(|&n| n.max(cap)).sum::<u32>())
+            .max()
+            .unwrap_or(0);
+        let ln = LnFactorials::new(max_total);
+        let games_stats = games.iter().map(|g| game_stats(&ln, g, cap)).collect_vec();
+        let mut candidates = vec![bag.clone()];
+        for g in &games_stats {
+            if !candidates.contains(&g.likeliest) {
+                candidates.push(g.likeliest.clone());
             }
         }
-        sum += gid;
-    }
-    println!("part1: {sum}")
-}
-
-fn part2(input: &str) {
-    let mut sum = 0u32;
-    for line in input.lines() {
-        let (_, sets) = line.split_once(": ").unwrap();
-        let mut red = 0u32;
-        let mut green = 0u32;
-        let mut blue = 0u32;
-        for set in sets.split("; ") {
-            for cubes in set.split(", ") {
-                let (count, color) = cubes.split_once(' ').unwrap();
-                let count: u32 = count.parse().unwrap();
-                match color {
-                    "red" if red < count => red = count,
-                    "green" if green < count => green = count,
-                    "blue" if blue < count => blue = count,
-                    _ => {}
-                };
+        let consistent = games
+            .iter()
+            .map(|g| {
+                let fits = |b: &Bag| g.draws.iter().filter(|d| b.contains(d)).count();
+                candidates.iter().map(fits).collect()
+            })
+            .collect();
+        let mut colours: BTreeMap<String, BTreeMap<u32, usize>> = BTreeMap::new();
+        let mut constraining = vec![];
+        for game in games {
+            for (i, draw) in game.draws.iter().enumerate() {
+                for (colour, &n) in &draw.0 {
+                    *colours
+                        .entry(colour.clone())
+                        .or_default()
+                        .entry(n)
+                        .or_default() += 1;
+                }
+                let tightness = draw
+                    .0
+                    .iter()
+                    .map(|(colour, &n)| n as f64 / bag.get(colour) as f64)
+                    .fold(0.0, f64::max);
+                constraining.push((game.id, i + 1, tightness));
             }
         }
-        sum += red * green * blue;
+        constraining.sort_by(|a, b| b.2.total_cmp(&a.2));
+        constraining.truncate(5);
+        Self {
+            games: games_stats,
+            candidates,
+            consistent,
+            colours,
+            constraining,
+        }
+    }
+
+    fn table(&self) -> String {
+        let mut out = format!(
+            "{:>5} {:>5} {:<28} {:<28} {:>9}\n",
+            "game", "draws", "min bag", "likeliest bag", "ln L"
+        );
+        for g in &self.games {
+            out += &format!(
+                "{:>5} {:>5} {:<28} {:<28} {:>9.3}\n",
+                g.id,
+                g.draws,
+                g.min_bag.to_string(),
+                g.likeliest.to_string(),
+                g.log_likelihood
+            );
+        }
+        out += "\ncandidate bags (#0 is the configured one):\n";
+        for (i, bag) in self.candidates.iter().enumerate() {
+            out += &format!("{:>5} {bag}\n", format!("#{i}"));
+        }
+        out += "\ndraws consistent with each candidate:\n";
+        out += &format!(
+            "{:>5} {}\n",
+            "game",
+            (0..self.candidates.len())
+                .map(|i| format!("{:>5}", format!("#{i}")))
+                .join(" ")
+        );
+        for (g, counts) in self.games.iter().zip(&self.consistent) {
+            let counts = counts
+                .iter()
+                .map(|n| format!("{:>5}", format!("{n}/{}", g.draws)));
+            out += &format!("{:>5} {}\n", g.id, counts.format(" "));
+        }
+        out += "\ncolour counts (count x draws):\n";
+        for (colour, counts) in &self.colours {
+            let draws = counts.values().sum::<usize>();
+            let mean = counts
+                .iter()
+                .map(|(&n, &k)| (n as usize * k) as f64)
+                .sum::<f64>()
+                / draws as f64;
+            let counts = counts.iter().map(|(n, k)| format!("{n}x{k}")).join(" ");
+            out += &format!("{colour:>8}: mean {mean:.2} over {draws} draws; {counts}\n");
+        }
+        out += "\nmost constraining draws:\n";
+        for (game, draw, tightness) in &self.constraining {
+            out += &format!(
+                "  game {game} draw {draw}: {:.0}% of the bag\n",
+                tightness * 100.0
+            );
+        }
+        out
+    }
+
+    fn json(&self) -> String {
+        let bag = |b: &Bag| {
+            let fields = b.0.iter().map(|(c, n)| format!("{}: {n}", quote(c)));
+            format!("{{{}}}", fields.format(", "))
+        };
+        let games = self
+            .games
+            .iter()
+            .zip(&self.consistent)
+            .map(|(g, consistent)| {
+                format!(
+                    "{{\"id\": {}, \"draws\": {}, \"min_bag\": {}, \"likeliest\": {}, \
+                 \"log_likelihood\": {}, \"consistent\": [{}]}}",
+                    g.id,
+                    g.draws,
+                    bag(&g.min_bag),
+                    bag(&g.likeliest),
+                    number(g.log_likelihood),
+                    consistent.iter().format(", ")
+                )
+            });
+        let colours = self.colours.iter().map(|(colour, counts)| {
+            let counts = counts.iter().map(|(n, k)| format!("\"{n}\": {k}"));
+            format!("{}: {{{}}}", quote(colour), counts.format(", "))
+        });
+        let constraining = self.constraining.iter().map(|(game, draw, t)| {
+            format!(
+                "{{\"game\": {game}, \"draw\": {draw}, \"tightness\": {}}}",
+                number(*t)
+            )
+        });
+        format!(
+            "{{\"games\": [{}], \"candidates\": [{}], \"colours\": {{{}}}, \"constraining\": [{}]}}",
+            games.format(", "),
+            self.candidates.iter().map(bag).format(", "),
+            colours.format(", "),
+            constraining.format(", ")
+        )
+    }
+}
+
+fn game_stats(ln: &LnFactorials, game: &Game, cap: u32) -> GameStats {
+    let min_bag = game.min_bag();
+    // colours never drawn only make the draws less likely, so they stay out
+    let (likeliest, log_likelihood) = min_bag
+        .0
+        .iter()
+        .map(|(colour, &n)| (n..=n.max(cap)).map(move |k| (colour.clone(), k)))
+        .multi_cartesian_product()
+        .map(|counts| Bag(counts.into_iter().collect()))
+        .map(|b| {
+            let l = ln.game(&b, game);
+            (b, l)
+        })
+        .fold((min_bag.clone(), f64::NEG_INFINITY), |best, (b, l)| {
+            if l > best.1 + 1e-9 {
+                (b, l)
+            } else {
+                best
+            }
+        });
+    GameStats {
+        id: game.id,
+        draws: game.draws.len(),
+        min_bag,
+        likeliest,
+        log_likelihood,
+    }
+}
+
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// JSON has no infinities, an impossible bag becomes `null`.
+fn number(x: f64) -> String {
+    if x.is_finite() {
+        format!("{x}")
+    } else {
+        "null".to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &str = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
+
+    #[test]
+    fn parts() {
+        let games = parse_games(INPUT).unwrap();
+        let bag = Bag::parse(PUZZLE_BAG).unwrap();
+        assert_eq!(part1(&games, &bag), 8);
+        assert_eq!(part2(&games, &bag), 2286);
+    }
+
+    #[test]
+    fn missing_colour() {
+        // never shows any blue, so its power is zero as with red * green * blue
+        let games =
+            parse_games("Game 1: 3 red, 2 green; 1 red\nGame 2: 1 red, 2 green, 3 blue").unwrap();
+        let bag = Bag::parse(PUZZLE_BAG).unwrap();
+        assert_eq!(games[0].min_bag().power(&bag), 0);
+        assert_eq!(part2(&games, &bag), 6);
+        let red_green = Bag::parse("5 red, 5 green").unwrap();
+        assert_eq!(games[0].min_bag().power(&red_green), 6);
+    }
+
+    #[test]
+    fn queries() {
+        let games = parse_games(INPUT).unwrap();
+        assert_eq!(
+            games[0].min_bag(),
+            Bag::parse("4 red, 2 green, 6 blue").unwrap()
+        );
+        // a colour the bag doesn't list makes a game impossible
+        let purple = parse_games("Game 7: 1 purple, 2 red").unwrap();
+        assert!(!purple[0].possible_with(&Bag::parse(PUZZLE_BAG).unwrap()));
+        assert_eq!(part2(&purple, &Bag::parse(PUZZLE_BAG).unwrap()), 0);
+        assert_eq!(part2(&purple, &Bag::default()), 2);
+
+        assert_eq!(smallest_bag(&games, 0), Some(Bag::default()));
+        let bag = smallest_bag(&games, 2).unwrap();
+        assert_eq!(bag.to_string(), "4 blue, 3 green, 6 red");
+        assert_eq!(possible(&games, &bag).map(|g| g.id).collect_vec(), [2, 5]);
+        let bag = smallest_bag(&games, 5).unwrap();
+        assert_eq!(bag, Bag::parse("20 red, 13 green, 15 blue").unwrap());
+        assert_eq!(smallest_bag(&games, 6), None);
+        assert_eq!(
+            smallest_bag(&purple, 1),
+            Some(Bag::parse("1 purple, 2 red").unwrap())
+        );
+    }
+
+    #[test]
+    fn likelihood() {
+        let ln = LnFactorials::new(10);
+        // one red from 1 red and 1 blue: 1/2
+        let bag = Bag::parse("1 red, 1 blue").unwrap();
+        let draw = Bag::parse("1 red").unwrap();
+        assert!((ln.draw(&bag, &draw) - 0.5f64.ln()).abs() < 1e-12);
+        assert_eq!(ln.draw(&draw, &bag), f64::NEG_INFINITY);
+
+        // always exactly 2 red and 1 blue: nothing else in the bag explains that best
+        let game = &parse_games("Game 9: 2 red, 1 blue; 1 blue, 2 red").unwrap()[0];
+        let stats = game_stats(&ln, game, 5);
+        assert_eq!(stats.likeliest, Bag::parse("2 red, 1 blue").unwrap());
+        assert_eq!(stats.log_likelihood, 0.0);
+    }
+
+    #[test]
+    fn stats() {
+        let games = parse_games(INPUT).unwrap();
+        let stats = Stats::new(&games, &Bag::parse(PUZZLE_BAG).unwrap(), 8);
+        // the configured bag, then the likeliest bag of each game
+        assert_eq!(stats.candidates.len(), 6);
+        assert_eq!(stats.candidates[1], stats.games[0].likeliest);
+        assert_eq!(
+            stats.consistent.iter().map(|c| c[0]).collect_vec(),
+            [3, 3, 2, 2, 2]
+        );
+        // only its own likeliest bag explains all of game 3
+        assert_eq!(stats.consistent[2], [2, 0, 0, 3, 0, 0]);
+        assert!(stats
+            .table()
+            .contains("    3   2/3   0/3   0/3   3/3   0/3   0/3\n"));
+        assert_eq!(stats.colours["red"][&1], 4);
+        // 20 red against a limit of 12
+        assert_eq!(stats.constraining[0].0, 3);
+        assert!(stats.table().contains("most constraining"));
+        let json = stats.json();
+        assert!(
+            json.starts_with("{\"games\": [{\"id\": 1, \"draws\": 3, \"min_bag\": {\"blue\": 6, ")
+        );
+        assert!(json.contains("\"consistent\": [2, 0, 0, 3, 0, 0]}"));
+        assert!(json.contains("\"candidates\": [{\"blue\": 14, \"green\": 13, \"red\": 12}, "));
     }
-    println!("part2: {sum}")
 }
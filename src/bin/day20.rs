@@ -1,22 +1,31 @@
 use std::cell::RefCell;
 use std::collections::{HashMap, VecDeque};
-use std::ops::{Deref, DerefMut};
+use std::env;
+use std::ops::DerefMut;
 
 use itertools::Itertools;
-use num::integer::lcm;
+use petgraph::prelude::DiGraphMap;
+use petgraph::Direction::Incoming;
 
 use aoc_2023_rust::aoc;
+use aoc_2023_rust::graph::{self, Highlight};
+use aoc_2023_rust::numtheory::{self, Hits};
+use aoc_2023_rust::search;
 
 fn main() {
     let aoc = aoc().unwrap();
     let input = aoc.read_input(20).unwrap();
     let modules = parse_modules(&input);
+    if env::args().any(|a| a == "--dot") {
+        print!("{}", dot(&modules));
+        return;
+    }
     println!("part1: {}", part1(&modules));
     let modules = parse_modules(&input);
     println!("part2: {}", part2(&modules));
 }
 
-fn parse_modules(input: &str) -> HashMap<&str, Module> {
+fn parse_modules(input: &str) -> HashMap<&str, Module<'_>> {
     let modules: HashMap<_, _> = input
         .lines()
         .map(Module::parse)
@@ -34,23 +43,52 @@ fn parse_modules(input: &str) -> HashMap<&str, Module> {
     modules
 }
 
+fn network<'a>(modules: &HashMap<&str, Module<'a>>) -> DiGraphMap<&'a str, ()> {
+    modules
+        .values()
+        .flat_map(|m| m.dst.iter().map(|&dst| (m.name, dst)))
+        .collect()
+}
+
+/// The module network as Graphviz, with every feedback loop of more than one module marked.
+fn dot(modules: &HashMap<&str, Module>) -> String {
+    let g = network(modules);
+    let loops = graph::sccs(&g)
+        .into_iter()
+        .filter(|c| c.len() > 1)
+        .collect_vec();
+    let summary = format!(
+        "// feedback loops of sizes {:?}\n",
+        loops.iter().map(Vec::len).collect_vec()
+    );
+    let highlight = Highlight::default().edges(
+        g.all_edges()
+            .filter(|(a, b, _)| loops.iter().any(|c| c.contains(a) && c.contains(b)))
+            .map(|(a, b, _)| (a, b)),
+    );
+    summary + &graph::to_dot(&g, &highlight, |_| None)
+}
+
 fn part1(modules: &HashMap<&str, Module>) -> usize {
     let mut cnt = Counter::default();
     for _ in 0..1000 {
-        pulse1(modules, &mut cnt);
+        press(modules, |_, _, high| {
+            if high {
+                cnt.high += 1;
+            } else {
+                cnt.low += 1;
+            }
+        });
     }
     cnt.low * cnt.high
 }
 
-fn pulse1(mm: &HashMap<&str, Module>, cnt: &mut Counter) {
+/// Pushes the button once, calling `on_pulse(src, dst, high)` for every pulse sent.
+fn press<'a>(mm: &HashMap<&str, Module<'a>>, mut on_pulse: impl FnMut(&str, &str, bool)) {
     let mut q = VecDeque::new();
     q.push_back(("button", "broadcaster", false));
     while let Some((src, dst, high)) = q.pop_front() {
-        if high {
-            cnt.high += 1;
-        } else {
-            cnt.low += 1;
-        }
+        on_pulse(src, dst, high);
         if let Some(m) = mm.get(dst) {
             let output = m.typ.borrow_mut().output(src, high);
             if let Some(output) = output {
@@ -63,54 +101,79 @@ fn pulse1(mm: &HashMap<&str, Module>, cnt: &mut Counter) {
     }
 }
 
-fn part2(modules: &HashMap<&str, Module>) -> usize {
-    impl<'a> ModuleType<'a> {
-        fn conj_states(&self) -> &HashMap<&'a str, bool> {
-            match self {
-                ModuleType::Conjunction { states } => states,
-                _ => unreachable!(),
-            }
-        }
-    }
+fn part2(modules: &HashMap<&str, Module>) -> u64 {
     let rx_conj = &modules
         .values()
         .filter(|m| m.dst.contains(&"rx"))
         .exactly_one()
         .unwrap();
-    let mut rx_cnt = rx_conj
+    let inputs = rx_conj
         .typ
         .borrow()
         .conj_states()
         .keys()
-        .map(|&k| (k, 0usize))
-        .collect::<HashMap<_, _>>();
-    let mut cnt = 0usize;
-    loop {
-        cnt += 1;
-        pulse2(modules, cnt, &mut rx_cnt);
-        if rx_cnt.values().all(|v| *v != 0) {
-            return rx_cnt.values().fold(1, |acc, v| lcm(acc, *v));
-        }
-    }
+        .copied()
+        .collect_vec();
+    let hits = inputs
+        .into_iter()
+        .map(|input| input_hits(modules, input))
+        .collect_vec();
+    numtheory::first_common(&hits).unwrap()
 }
 
-fn pulse2<'a>(mm: &'a HashMap<&'a str, Module>, cnt: usize, rx_cnt: &mut HashMap<&'a str, usize>) {
-    let mut q = VecDeque::new();
-    q.push_back(("button", "broadcaster", false));
-    while let Some((src, dst, high)) = q.pop_front() {
-        if high {
-            if let Some(c) = rx_cnt.get_mut(src) {
-                if *c == 0 {
-                    *c = cnt;
-                }
+/// The presses during which `input` sends a high pulse.
+///
+/// What `input` sends depends only on the modules upstream of it, so their state alone is run
+/// until it cycles.
+fn input_hits(modules: &HashMap<&str, Module>, input: &str) -> Hits {
+    let g = network(modules);
+    let mut upstream = search::bfs([input], |&n| g.neighbors_directed(n, Incoming))
+        .into_map()
+        .into_keys()
+        .collect_vec();
+    upstream.sort_unstable();
+    // pressing moves every module on, so put them all back afterwards
+    let all = modules.keys().copied().sorted().collect_vec();
+    let before = snapshot(modules, &all);
+    // the state after some presses, and whether `input` went high during the last one
+    let start = (snapshot(modules, &upstream), false);
+    let hits = Hits::find(
+        start,
+        |(state, _)| {
+            restore(modules, &upstream, state);
+            let mut hit = false;
+            press(modules, |src, _, high| hit |= high && src == input);
+            (snapshot(modules, &upstream), hit)
+        },
+        |&(_, hit)| hit,
+    );
+    restore(modules, &all, &before);
+    hits
+}
+
+fn snapshot(modules: &HashMap<&str, Module>, names: &[&str]) -> Vec<bool> {
+    let mut state = vec![];
+    for m in names.iter().filter_map(|n| modules.get(n)) {
+        match &*m.typ.borrow() {
+            ModuleType::Broadcaster => {}
+            ModuleType::FlipFlop { state: on } => state.push(*on),
+            ModuleType::Conjunction { states } => {
+                state.extend(states.iter().sorted().map(|(_, &high)| high))
             }
         }
-        if let Some(m) = mm.get(dst) {
-            let output = m.typ.borrow_mut().output(src, high);
-            if let Some(output) = output {
-                let src = dst;
-                for dst in &m.dst {
-                    q.push_back((src, dst, output));
+    }
+    state
+}
+
+fn restore(modules: &HashMap<&str, Module>, names: &[&str], state: &[bool]) {
+    let mut state = state.iter().copied();
+    for m in names.iter().filter_map(|n| modules.get(n)) {
+        match m.typ.borrow_mut().deref_mut() {
+            ModuleType::Broadcaster => {}
+            ModuleType::FlipFlop { state: on } => *on = state.next().unwrap(),
+            ModuleType::Conjunction { states } => {
+                for (_, high) in states.iter_mut().sorted_by_key(|(&k, _)| k) {
+                    *high = state.next().unwrap();
                 }
             }
         }
@@ -140,7 +203,7 @@ impl<'a> Module<'a> {
             .map(ModuleType::parse)
             .map(RefCell::new)
             .unwrap();
-        let name = m.trim_start_matches(|c| c == '%' || c == '&');
+        let name = m.trim_start_matches(['%', '&']);
         Self { name, typ, dst }
     }
 }
@@ -180,6 +243,13 @@ impl<'a> ModuleType<'a> {
             }
         }
     }
+
+    fn conj_states(&self) -> &HashMap<&'a str, bool> {
+        match self {
+            ModuleType::Conjunction { states } => states,
+            _ => unreachable!(),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -203,5 +273,56 @@ mod tests {
         let input2 = parse_modules(INPUT2);
         assert_eq!(part1(&input1), 32000000);
         assert_eq!(part1(&input2), 11687500);
+
+        let sccs = graph::sccs(&network(&input1));
+        let ring = sccs.iter().find(|c| c.len() > 1).unwrap();
+        assert_eq!(
+            ring.iter().sorted().collect_vec(),
+            [&"a", &"b", &"c", &"inv"]
+        );
+        assert!(dot(&input1).starts_with("// feedback loops of sizes [4]\ndigraph {"));
+        assert!(dot(&input2).contains("\"a\" -> \"con\";"));
+    }
+
+    // `ia` goes high at presses 5 and 7 of every 8, `ib` at presses 11 and 15 of every 16
+    const COUNTERS: &str = "broadcaster -> a0, b0
+%a0 -> a1, ka
+%a1 -> a2
+%a2 -> ka
+&ka -> ia
+&ia -> final
+%b0 -> b1, kb
+%b1 -> b2, kb
+%b2 -> b3
+%b3 -> kb
+&kb -> ib
+&ib -> final
+&final -> rx";
+
+    #[test]
+    fn p2() {
+        let modules = parse_modules(COUNTERS);
+        let ia = input_hits(&modules, "ia");
+        assert_eq!(
+            (1..=16).filter(|&t| ia.contains(t)).collect_vec(),
+            [5, 7, 13, 15]
+        );
+        let ib = input_hits(&modules, "ib");
+        assert_eq!(
+            (1..=32).filter(|&t| ib.contains(t)).collect_vec(),
+            [11, 15, 27, 31]
+        );
+
+        // the first two hits would suggest every other press for `ia`, and 11 overall
+        let answer = part2(&parse_modules(COUNTERS));
+        assert_eq!(answer, 15);
+        let modules = parse_modules(COUNTERS);
+        let mut presses = 0;
+        let mut low_to_rx = false;
+        while !low_to_rx {
+            presses += 1;
+            press(&modules, |_, dst, high| low_to_rx |= dst == "rx" && !high);
+        }
+        assert_eq!(presses, answer);
     }
 }
@@ -1,105 +1,58 @@
-use std::collections::{HashSet, VecDeque};
-use std::ops::{Deref, DerefMut, Index};
-
-use itertools::Itertools;
-use polyfit_rs::polyfit_rs::polyfit;
-
 use aoc_2023_rust::aoc;
+use aoc_2023_rust::grid::{self, GridExt};
+use aoc_2023_rust::plane::{signed, Plane};
+use aoc_2023_rust::poly::Differences;
+use aoc_2023_rust::viz::{self, Frame, Style};
 
 fn main() {
     let aoc = aoc().unwrap();
     let input = aoc.read_input(21).unwrap();
     let grid = parse_grid(&input);
+    if let Some(viz) = viz::Output::from_args().unwrap() {
+        viz.emit(&[frame(&grid, 64)]).unwrap();
+        return;
+    }
     println!("part1: {}", part1(&grid, 64));
     println!("part2: {}", part2(&grid));
 }
 
-struct Grid(grid::Grid<char>);
+type Grid = Plane<char>;
 
 fn parse_grid(input: &str) -> Grid {
-    Grid(
-        input
-            .lines()
-            .map(|line| line.chars().collect())
-            .collect_vec()
-            .into(),
-    )
+    Plane::repeat(grid::parse(input))
 }
 
-type Pos = (isize, isize);
-
 fn part1(grid: &Grid, steps: usize) -> usize {
-    let oddness = steps % 2;
-    let mut cnt = 0;
-    let mut vis = HashSet::new();
-    let mut q = VecDeque::new();
-    let start = grid.indexed_iter().find(|(_, &c)| c == 'S').unwrap().0;
-    let start = (start.0 as isize, start.1 as isize);
-    q.push_back((start, 0));
-    while let Some((pos, step)) = q.pop_front() {
-        if step > steps {
-            break;
-        }
-        if vis.contains(&pos) {
-            continue;
-        }
-        vis.insert(pos);
-        if step % 2 == oddness {
-            cnt += 1;
-        }
-        for next in grid.neighbors(pos) {
-            if grid[next] != '#' {
-                q.push_back((next, step + 1));
-            }
-        }
-    }
-    cnt
+    let start = signed(grid.position(|&c| c == 'S').unwrap());
+    grid.distances(start, steps, |&c| c != '#')
+        .values()
+        .filter(|&&d| d % 2 == steps % 2)
+        .count()
 }
 
-fn part2(grid: &Grid) -> usize {
-    let steps = 65;
-    let x = [0, 1, 2, 3].map(|x| steps + x * grid.size().0);
-    let y = x.map(|x| part1(grid, x) as f64);
-    let coefficients = polyfit(&x.map(|x| x as f64), &y, 2).unwrap();
-    let x = 26501365f64;
-    (coefficients[2] * x * x + coefficients[1] * x + coefficients[0]) as usize
+/// The garden with the plots reachable in exactly `steps` marked, on the starting tile only.
+fn frame(grid: &Grid, steps: usize) -> Frame {
+    let start = signed(grid.position(|&c| c == 'S').unwrap());
+    let plots = grid
+        .distances(start, steps, |&c| c != '#')
+        .into_iter()
+        .filter(|&(_, d)| d % 2 == steps % 2)
+        .filter_map(|((r, c), _)| Some((usize::try_from(r).ok()?, usize::try_from(c).ok()?)));
+    Frame::chars(grid).overlay(plots, Style::bg(viz::GREEN))
 }
 
-impl Grid {
-    fn neighbors(&self, pos: Pos) -> [Pos; 4] {
-        let (row, col) = pos;
-        [
-            (row - 1, col),
-            (row + 1, col),
-            (row, col - 1),
-            (row, col + 1),
-        ]
-    }
+fn part2(grid: &Grid) -> i128 {
+    reachable_far(grid, 26501365)
 }
 
-impl Index<Pos> for Grid {
-    type Output = char;
-
-    fn index(&self, index: Pos) -> &Self::Output {
-        let (row, col) = index;
-        let row = row.rem_euclid(self.rows() as isize) as usize;
-        let col = col.rem_euclid(self.cols() as isize) as usize;
-        &self.0[(row, col)]
-    }
-}
-
-impl Deref for Grid {
-    type Target = grid::Grid<char>;
-
-    fn deref(&self) -> &Self::Target {
-        &self.0
-    }
-}
-
-impl DerefMut for Grid {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
-    }
+/// Once the walk spans whole tiles, the reachable count grows quadratically per tile width, so
+/// it is sampled at `steps % width` plus a few widths and extrapolated exactly.
+fn reachable_far(grid: &Grid, steps: usize) -> i128 {
+    let width = grid.size().0;
+    let offset = steps % width;
+    let y = [0, 1, 2, 3].map(|k| part1(grid, offset + k * width) as i128);
+    let poly = Differences::new(&y, Some(2)).unwrap();
+    poly.at((steps / width) as i128).unwrap()
 }
 
 #[cfg(test)]
@@ -122,5 +75,7 @@ mod tests {
     fn test() {
         let grid = parse_grid(INPUT);
         assert_eq!(part1(&grid, 6), 16);
+        assert_eq!(part1(&grid, 10), 50);
+        assert_eq!(part1(&grid, 50), 1594);
     }
 }
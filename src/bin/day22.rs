@@ -3,6 +3,7 @@ use std::cmp::{max, min};
 use itertools::{Either, Itertools};
 
 use aoc_2023_rust::aoc;
+use aoc_2023_rust::grid::Grid;
 
 fn main() {
     let aoc = aoc().unwrap();
@@ -12,7 +13,7 @@ fn main() {
     println!("part2: {}", part2(&boxes));
 }
 
-type HeightMap = grid::Grid<usize>;
+type HeightMap = Grid<usize>;
 
 fn parse_input(input: &str) -> Vec<[[usize; 3]; 2]> {
     let mut boxes = input.lines().map(parse_box).collect_vec();
@@ -1,9 +1,8 @@
 use std::thread;
 
-use grid::Grid;
-use itertools::Itertools;
-
 use aoc_2023_rust::aoc;
+use aoc_2023_rust::grid::{self, Direction, Grid, GridExt, Pos};
+use aoc_2023_rust::viz::{self, Frame, Style};
 
 fn main() {
     thread::Builder::new()
@@ -18,16 +17,17 @@ fn _main() {
     let aoc = aoc().unwrap();
     let input = aoc.read_input(23).unwrap();
     let grid = parse_grid(&input);
+    if let Some(viz) = viz::Output::from_args().unwrap() {
+        viz.emit(&[frame(&grid, false), frame(&grid, true)])
+            .unwrap();
+        return;
+    }
     println!("part1: {}", solve(&grid, false));
     println!("part2: {}", solve(&grid, true));
 }
 
 fn parse_grid(input: &str) -> Grid<char> {
-    input
-        .lines()
-        .map(|line| line.chars().collect_vec())
-        .collect_vec()
-        .into()
+    grid::parse(input)
 }
 
 fn solve(grid: &Grid<char>, ignore_slopes: bool) -> usize {
@@ -59,61 +59,71 @@ fn dfs(grid: &Grid<char>, pos: Pos, d: usize, vis: &mut Grid<bool>, ignore_slope
     max
 }
 
-type Pos = (usize, usize);
-
-enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
+/// Every tile of the longest hike from the top to the bottom, empty if there is none.
+///
+/// Like `solve`, but keeps the path too, which only `--viz` needs.
+fn longest_hike(grid: &Grid<char>, ignore_slopes: bool) -> Vec<Pos> {
+    let mut best = vec![];
+    hike_dfs(
+        grid,
+        (0, 1),
+        &mut vec![],
+        &mut best,
+        &mut Grid::init(grid.rows(), grid.cols(), false),
+        ignore_slopes,
+    );
+    best
 }
 
-impl Direction {
-    fn offset(&self) -> (isize, isize) {
-        match self {
-            Direction::Up => (-1, 0),
-            Direction::Down => (1, 0),
-            Direction::Left => (0, -1),
-            Direction::Right => (0, 1),
+fn hike_dfs(
+    grid: &Grid<char>,
+    pos: Pos,
+    path: &mut Vec<Pos>,
+    best: &mut Vec<Pos>,
+    vis: &mut Grid<bool>,
+    ignore_slopes: bool,
+) {
+    path.push(pos);
+    if pos == (grid.rows() - 1, grid.cols() - 2) {
+        if path.len() > best.len() {
+            best.clone_from(path);
         }
-    }
-    fn nav(&self, pos: Pos) -> Option<Pos> {
-        let (r, c) = pos;
-        let (dr, dc) = self.offset();
-        r.checked_add_signed(dr).zip(c.checked_add_signed(dc))
-    }
-
-    fn ways(c: char) -> &'static [Direction] {
-        match c {
-            '^' => &[Direction::Up],
-            'v' => &[Direction::Down],
-            '<' => &[Direction::Left],
-            '>' => &[Direction::Right],
-            _ => Self::all_ways(),
+    } else {
+        vis[pos] = true;
+        for adj in adj(grid, pos, ignore_slopes) {
+            if !vis[adj] {
+                hike_dfs(grid, adj, path, best, vis, ignore_slopes);
+            }
         }
+        vis[pos] = false;
     }
+    path.pop();
+}
+
+fn frame(grid: &Grid<char>, ignore_slopes: bool) -> Frame {
+    let hike = longest_hike(grid, ignore_slopes);
+    let color = if ignore_slopes { viz::BLUE } else { viz::GREEN };
+    Frame::chars(grid).overlay(hike, Style::bg(color))
+}
 
-    fn all_ways() -> &'static [Direction] {
-        &[
-            Direction::Up,
-            Direction::Down,
-            Direction::Left,
-            Direction::Right,
-        ]
+fn ways(c: char) -> &'static [Direction] {
+    match c {
+        '^' => &[Direction::Up],
+        'v' => &[Direction::Down],
+        '<' => &[Direction::Left],
+        '>' => &[Direction::Right],
+        _ => &Direction::ALL,
     }
 }
 
 fn adj(grid: &Grid<char>, pos: Pos, ignore_slope: bool) -> impl Iterator<Item = Pos> + '_ {
     if ignore_slope {
-        Direction::all_ways()
+        &Direction::ALL
     } else {
-        Direction::ways(grid[pos])
+        ways(grid[pos])
     }
     .iter()
-    .filter_map(move |d| {
-        d.nav(pos)
-            .filter(|&(r, c)| grid.get(r, c).is_some_and(|&c| c != '#'))
-    })
+    .filter_map(move |&d| grid.nav(pos, d).filter(|&p| grid[p] != '#'))
 }
 
 #[cfg(test)]
@@ -149,5 +159,10 @@ mod tests {
         let grid = parse_grid(INPUT);
         assert_eq!(solve(&grid, false), 94);
         assert_eq!(solve(&grid, true), 154);
+        let hike = longest_hike(&grid, false);
+        assert_eq!(hike.len(), 95);
+        assert_eq!((hike[0], hike[94]), ((0, 1), (22, 21)));
+        assert_eq!(longest_hike(&grid, true).len(), 155);
+        assert!(hike.iter().all(|&p| grid[p] != '#'));
     }
 }
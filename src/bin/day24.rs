@@ -1,10 +1,8 @@
-use std::ops::Neg;
-
 use itertools::Itertools;
-use nalgebra::{vector, SMatrix, Vector2, Vector3, Vector6};
-use num::Zero;
+use nalgebra::{matrix, vector, Matrix3, Vector2, Vector3};
+use num::{BigInt, BigRational, ToPrimitive};
 
-use aoc_2023_rust::aoc;
+use aoc_2023_rust::{aoc, linalg, parse};
 
 fn main() {
     let aoc = aoc().unwrap();
@@ -18,6 +16,20 @@ fn main() {
 }
 
 struct Hailstone {
+    p: Vector3<i64>,
+    v: Vector3<i64>,
+}
+
+impl Hailstone {
+    fn float(&self) -> Float {
+        Float {
+            p: self.p.cast(),
+            v: self.v.cast(),
+        }
+    }
+}
+
+struct Float {
     p: Vector3<f64>,
     v: Vector3<f64>,
 }
@@ -26,14 +38,12 @@ fn parse(input: &str) -> Vec<Hailstone> {
     input
         .lines()
         .map(|line| {
-            let (p, v) = line.split_once(" @ ").unwrap();
-
-            fn parse_v3(s: &str) -> Vector3<f64> {
-                Vector3::from_iterator(s.split(", ").map(|v| v.parse().unwrap()))
-            }
+            let nums = parse::ints::<i64>(line).unwrap();
+            assert_eq!(nums.len(), 6, "bad hailstone: {line}");
+            let v3 = |n: &[i64]| Vector3::from_column_slice(n);
             Hailstone {
-                p: parse_v3(p),
-                v: parse_v3(v),
+                p: v3(&nums[..3]),
+                v: v3(&nums[3..]),
             }
         })
         .collect_vec()
@@ -48,6 +58,7 @@ fn parse(input: &str) -> Vec<Hailstone> {
 // x(a1 - a2) = b2 - b1
 // x = (b2 - b1) / (a1 - a2)
 fn collide_2d(a: &Hailstone, b: &Hailstone, bound: &Vector2<f64>) -> Option<Vector2<f64>> {
+    let (a, b) = (a.float(), b.float());
     let a1 = a.v.y / a.v.x;
     let b1 = a.p.y - a1 * a.p.x;
     let a2 = b.v.y / b.v.x;
@@ -76,7 +87,7 @@ fn part1(stones: &[Hailstone], bound: &Vector2<f64>) -> usize {
         .count()
 }
 
-fn part2(stones: &[Hailstone]) -> f64 {
+fn part2(stones: &[Hailstone]) -> i128 {
     // P + t[i] * V = p[i] + t[i] * v[i]
     // P - p[i] = t[i] * (v[i] - V)
     // cross product both sides with (v[i] - V)
@@ -94,31 +105,75 @@ fn part2(stones: &[Hailstone]) -> f64 {
     // M * x = b
     // | -(v[i] - v[j]), p[i] - p[j] | | P | = | p[i] x v[i] - p[j] x v[j] |
     // | -(v[i] - v[k]), p[i] - p[k] | | V | = | p[i] x v[i] - p[k] x v[k] |
-    // x = M^-1 * b
-
-    let mut m = SMatrix::<f64, 6, 6>::zero();
-    m.fixed_view_mut::<3, 3>(0, 0)
-        .copy_from(&(stones[0].v - stones[1].v).neg().cross_matrix());
-    m.fixed_view_mut::<3, 3>(0, 3)
-        .copy_from(&(stones[0].p - stones[1].p).cross_matrix());
-    m.fixed_view_mut::<3, 3>(3, 0)
-        .copy_from(&(stones[0].v - stones[2].v).neg().cross_matrix());
-    m.fixed_view_mut::<3, 3>(3, 3)
-        .copy_from(&(stones[0].p - stones[2].p).cross_matrix());
-    let mut b = Vector6::zero();
-    b.fixed_view_mut::<3, 1>(0, 0)
-        .copy_from(&(stones[0].p.cross(&stones[0].v) - stones[1].p.cross(&stones[1].v)));
-    b.fixed_view_mut::<3, 1>(3, 0)
-        .copy_from(&(stones[0].p.cross(&stones[0].v) - stones[2].p.cross(&stones[2].v)));
-
-    m.lu().solve_mut(&mut b);
-    let p = b.fixed_view::<3, 1>(0, 0);
-    let v = b.fixed_view::<3, 1>(3, 0);
-
-    dbg!(p.iter().collect_vec());
-    dbg!(v.iter().collect_vec());
-
-    p.iter().map(|x| x.round()).sum::<f64>()
+    // solved exactly, since the products are far beyond what f64 represents
+
+    // some triples are degenerate (e.g. parallel stones), any other will do
+    let (p, v) = stones
+        .iter()
+        .tuple_combinations()
+        .find_map(|(a, b, c)| throw_through(a, b, c))
+        .expect("no triple of hailstones determines the throw");
+    for stone in stones {
+        assert!(hits(p, v, stone), "the throw misses {:?}", stone.p);
+    }
+    p.iter().sum()
+}
+
+type V3 = [i128; 3];
+
+fn throw_through(a: &Hailstone, b: &Hailstone, c: &Hailstone) -> Option<(V3, V3)> {
+    let (pa, va) = (a.p.map(i128::from), a.v.map(i128::from));
+    let mut m = vec![];
+    let mut rhs = vec![];
+    for o in [b, c] {
+        let (po, vo) = (o.p.map(i128::from), o.v.map(i128::from));
+        let left = cross_matrix(&(vo - va));
+        let right = cross_matrix(&(pa - po));
+        let r = pa.cross(&va) - po.cross(&vo);
+        for i in 0..3 {
+            m.push(
+                (0..3)
+                    .map(|j| left[(i, j)])
+                    .chain((0..3).map(|j| right[(i, j)]))
+                    .map(|x| BigRational::from(BigInt::from(x)))
+                    .collect_vec(),
+            );
+            rhs.push(BigRational::from(BigInt::from(r[i])));
+        }
+    }
+    let x = linalg::solve(&m, &rhs).ok()?;
+    let x = x
+        .iter()
+        .map(|x| x.is_integer().then(|| x.to_integer().to_i128()).flatten())
+        .collect::<Option<Vec<_>>>()?;
+    Some(([x[0], x[1], x[2]], [x[3], x[4], x[5]]))
+}
+
+fn cross_matrix(v: &Vector3<i128>) -> Matrix3<i128> {
+    matrix![
+        0, -v.z, v.y;
+        v.z, 0, -v.x;
+        -v.y, v.x, 0
+    ]
+}
+
+/// Whether a rock thrown from `p` with velocity `v` meets `stone` at a single integral time.
+fn hits(p: V3, v: V3, stone: &Hailstone) -> bool {
+    let mut time = None;
+    for i in 0..3 {
+        let (dp, dv) = (i128::from(stone.p[i]) - p[i], v[i] - i128::from(stone.v[i]));
+        if dv == 0 {
+            if dp != 0 {
+                return false;
+            }
+            continue;
+        }
+        if dp % dv != 0 || dp / dv < 0 || time.is_some_and(|t| t != dp / dv) {
+            return false;
+        }
+        time = Some(dp / dv);
+    }
+    true
 }
 
 #[cfg(test)]
@@ -135,6 +190,6 @@ mod tests {
     fn test() {
         let stones = parse(INPUT);
         assert_eq!(part1(&stones, &vector![7.0, 27.0]), 2);
-        assert_eq!(part2(&stones), 47.0);
+        assert_eq!(part2(&stones), 47);
     }
 }
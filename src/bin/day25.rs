@@ -1,18 +1,26 @@
+use std::env;
+
 use petgraph::prelude::UnGraphMap;
-use rustworkx_core::connectivity::stoer_wagner_min_cut;
 
 use aoc_2023_rust::aoc;
+use aoc_2023_rust::graph::{self, Highlight};
 
 fn main() {
     let aoc = aoc().unwrap();
     let input = aoc.read_input(25).unwrap();
     let g = parse(&input);
+    if env::args().any(|a| a == "--dot") {
+        let cut = graph::min_cut(&g).unwrap();
+        let highlight = Highlight::default().edges(cut.edges);
+        print!("{}", graph::to_dot(&g, &highlight, |_| None));
+        return;
+    }
     println!("part1: {}", part1(&g));
 }
 
 type G<'a> = UnGraphMap<&'a str, ()>;
 
-fn parse(input: &str) -> G {
+fn parse(input: &str) -> G<'_> {
     UnGraphMap::from_edges(input.lines().flat_map(|line| {
         let (src, dst) = line.split_once(": ").unwrap();
         dst.split_ascii_whitespace().map(move |dst| (src, dst))
@@ -20,9 +28,9 @@ fn parse(input: &str) -> G {
 }
 
 fn part1(g: &G) -> usize {
-    let (cut, partition) = stoer_wagner_min_cut(g, |_| anyhow::Ok(1)).unwrap().unwrap();
-    assert_eq!(cut, 3);
-    partition.len() * (g.node_count() - partition.len())
+    let cut = graph::min_cut(g).unwrap();
+    assert_eq!(cut.edges.len(), 3);
+    cut.sides[0].len() * cut.sides[1].len()
 }
 
 #[cfg(test)]
@@ -47,5 +55,8 @@ frs: qnr lhk lsr";
     fn test() {
         let g = parse(INPUT);
         assert_eq!(part1(&g), 54);
+        let cut = [("hfx", "pzl"), ("bvb", "cmg"), ("nvd", "jqt")];
+        assert!(graph::verify_cut(&g, &cut).is_ok());
+        assert!(graph::verify_cut(&g, &cut[..2]).is_err());
     }
 }
@@ -1,85 +1,349 @@
-use aoc_2023_rust::aoc;
+use std::env;
+use std::fmt::Write;
+use std::str::FromStr;
+
+use anyhow::{Context, Result};
+use itertools::Itertools;
+
+use aoc_2023_rust::grid::{self, Grid, GridExt, Pos};
+use aoc_2023_rust::{aoc, parse};
 
 fn main() {
     let aoc = aoc().unwrap();
     let input = aoc.read_input(3).unwrap();
 
-    let mut nums = Num::parse(&input);
-    part1(&input, &mut nums);
-    part2(&input, &nums);
+    let schematic = Schematic::parse(&input);
+    if env::args().any(|a| a == "--report") {
+        print!("{}", schematic.report());
+        return;
+    }
+    // each `--rule "SYMBOL COUNT COMBINE"` replaces the gear rule, e.g. `--rule "# 3 sum"`
+    let rules = env::args()
+        .tuple_windows()
+        .filter(|(a, _)| a == "--rule")
+        .map(|(_, spec)| Rule::parse(&spec).unwrap())
+        .collect_vec();
+    let rules = if rules.is_empty() { vec![GEAR] } else { rules };
+    println!("part1: {}", part1(&schematic));
+    println!("part2: {}", part2(&schematic, &rules).unwrap());
 }
 
-#[derive(Debug)]
-struct Num {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Part {
     num: u32,
-    x: usize,
-    y: usize,
+    row: usize,
+    col: usize,
     len: usize,
-    found: bool,
 }
 
-impl Num {
-    fn parse(input: &str) -> Vec<Num> {
-        let mut nums: Vec<Num> = vec![];
-        input
-            .lines()
-            .enumerate()
-            .for_each(|(y, line)| Self::parse_line(y, line, &mut nums));
-        nums
-    }
-
-    fn parse_line(y: usize, line: &str, nums: &mut Vec<Num>) {
-        let mut chars = line.char_indices();
-        while let Some((i, _)) = chars.find(|(_, c)| c.is_numeric()) {
-            let e = match chars.find(|(_, c)| !c.is_numeric()) {
-                Some((j, _)) => j,
-                None => line.len(),
-            };
-            let num = line[i..e].parse().unwrap();
-            nums.push(Num {
-                num,
-                x: i,
-                y,
-                len: e - i,
-                found: false,
-            });
+impl Part {
+    /// Inside the box one cell larger than the number on every side.
+    fn touches(&self, (row, col): Pos) -> bool {
+        row + 1 >= self.row
+            && row <= self.row + 1
+            && col + 1 >= self.col
+            && col <= self.col + self.len
+    }
+}
+
+#[derive(Debug)]
+struct Schematic {
+    grid: Grid<char>,
+    parts: Vec<Part>,
+    /// The index into `parts` of the number covering each cell.
+    index: Grid<Option<usize>>,
+}
+
+impl Schematic {
+    fn parse(input: &str) -> Self {
+        let grid = grid::parse(input);
+        let mut parts = vec![];
+        let mut index = Grid::new(grid.rows(), grid.cols());
+        for (row, line) in grid.iter_rows().enumerate() {
+            let mut num = None;
+            for (col, c) in line.chain([&'.']).enumerate() {
+                match (c.to_digit(10), num.as_mut()) {
+                    (Some(d), Some(Part { num, len, .. })) => {
+                        *num = *num * 10 + d;
+                        *len += 1;
+                    }
+                    (Some(d), None) => {
+                        num = Some(Part {
+                            num: d,
+                            row,
+                            col,
+                            len: 1,
+                        })
+                    }
+                    (None, _) => {
+                        if let Some(part) = num.take() {
+                            for c in part.col..part.col + part.len {
+                                index[(row, c)] = Some(parts.len());
+                            }
+                            parts.push(part);
+                        }
+                    }
+                }
+            }
+        }
+        Self { grid, parts, index }
+    }
+
+    fn symbols(&self) -> impl Iterator<Item = (Pos, char)> + '_ {
+        self.grid
+            .indexed_iter()
+            .filter(|(_, c)| !c.is_ascii_digit() && **c != '.')
+            .map(|(pos, &c)| (pos, c))
+    }
+
+    /// The symbols next to each part, indexed like `parts`.
+    fn adjacency(&self) -> Vec<Vec<(Pos, char)>> {
+        let mut adjacent = vec![vec![]; self.parts.len()];
+        for (pos, c) in self.symbols() {
+            for i in self.parts_around(pos) {
+                adjacent[i].push((pos, c));
+            }
+        }
+        adjacent
+    }
+
+    /// Every part number with the symbols next to it, then the numbers next to none.
+    fn report(&self) -> String {
+        let mut out = String::new();
+        let mut alone = vec![];
+        for (part, symbols) in self.parts.iter().zip(self.adjacency()) {
+            let at = format!("{} at {:?}", part.num, (part.row, part.col));
+            if symbols.is_empty() {
+                alone.push(at);
+                continue;
+            }
+            let symbols = symbols
+                .iter()
+                .map(|(pos, c)| format!("{c} at {pos:?}"))
+                .join(", ");
+            writeln!(out, "{at}: {symbols}").unwrap();
+        }
+        writeln!(out, "adjacent to nothing: {}", alone.len()).unwrap();
+        for at in alone {
+            writeln!(out, "{at}").unwrap();
         }
+        out
     }
 
-    fn touches(&self, x: usize, y: usize) -> bool {
-        let by = y == self.y || y + 1 == self.y || self.y + 1 == y; // same line, above, below
-        let bx = x + 1 == self.x
-            || x == self.x + self.len - 1
-            || (x >= self.x && x <= self.x + self.len); // before, after, inside
-        by && bx
+    /// The distinct parts next to `pos`, in reading order.
+    fn parts_around(&self, pos: Pos) -> Vec<usize> {
+        let mut found: Vec<usize> = vec![];
+        for (_, p) in self.grid.neighbors8(pos) {
+            if let Some(i) = self.index[p] {
+                if !found.contains(&i) {
+                    found.push(i);
+                }
+            }
+        }
+        found.sort_unstable();
+        debug_assert!(found.iter().all(|&i| self.parts[i].touches(pos)));
+        found
     }
 }
 
-fn part1(input: &str, nums: &mut [Num]) {
-    input.lines().enumerate().for_each(|(y, line)| {
-        line.match_indices(|c: char| !c.is_numeric() && c != '.')
-            .for_each(|(x, _)| {
-                nums.iter_mut()
-                    .filter(|num| !num.found)
-                    .filter(|num| num.touches(x, y))
-                    .for_each(|num| num.found = true);
-            });
-    });
-    let sum: u32 = nums.iter().filter(|num| num.found).map(|num| num.num).sum();
-    println!("part1: {sum}")
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Combine {
+    Product,
+    Sum,
+    Min,
+    Max,
 }
 
-fn part2(input: &str, nums: &[Num]) {
-    let mut sum = 0u32;
-    input.lines().enumerate().for_each(|(y, line)| {
-        line.match_indices('*').for_each(|(x, _)| {
-            let mut parts = nums.iter().filter(|num| num.touches(x, y));
-            let a = parts.next();
-            let b = parts.next();
-            if let (Some(a), Some(b)) = (a, b) {
-                sum += a.num * b.num;
+impl Combine {
+    /// `None` if a sum or product overflows.
+    fn apply(self, mut nums: impl Iterator<Item = u64>) -> Option<u64> {
+        match self {
+            Self::Product => nums.try_fold(1, u64::checked_mul),
+            Self::Sum => nums.try_fold(0, u64::checked_add),
+            Self::Min => Some(nums.min().unwrap_or(0)),
+            Self::Max => Some(nums.max().unwrap_or(0)),
+        }
+    }
+}
+
+impl FromStr for Combine {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "product" => Ok(Self::Product),
+            "sum" => Ok(Self::Sum),
+            "min" => Ok(Self::Min),
+            "max" => Ok(Self::Max),
+            _ => Err("expected product, sum, min or max".to_owned()),
+        }
+    }
+}
+
+/// A `symbol` next to exactly `count` part numbers is worth those numbers combined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Rule {
+    symbol: char,
+    count: usize,
+    combine: Combine,
+}
+
+const GEAR: Rule = Rule {
+    symbol: '*',
+    count: 2,
+    combine: Combine::Product,
+};
+
+impl Rule {
+    fn parse(spec: &str) -> parse::Result<Self> {
+        let (symbol, count, combine) = parse::fields(spec)?;
+        Ok(Self {
+            symbol,
+            count,
+            combine,
+        })
+    }
+}
+
+fn part1(schematic: &Schematic) -> u32 {
+    schematic
+        .parts
+        .iter()
+        .zip(schematic.adjacency())
+        .filter(|(_, symbols)| !symbols.is_empty())
+        .map(|(part, _)| part.num)
+        .sum()
+}
+
+fn part2(schematic: &Schematic, rules: &[Rule]) -> Result<u64> {
+    let mut total: u64 = 0;
+    for (pos, c) in schematic.symbols() {
+        let parts = schematic.parts_around(pos);
+        for rule in rules
+            .iter()
+            .filter(|r| r.symbol == c && r.count == parts.len())
+        {
+            let nums = parts.iter().map(|&i| schematic.parts[i].num as u64);
+            total = rule
+                .combine
+                .apply(nums)
+                .and_then(|n| total.checked_add(n))
+                .with_context(|| format!("{rule:?} at {pos:?} overflows the total"))?;
+        }
+    }
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &str = "467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..";
+
+    #[test]
+    fn p1() {
+        assert_eq!(part1(&Schematic::parse(INPUT)), 4361)
+    }
+
+    #[test]
+    fn p2() {
+        assert_eq!(part2(&Schematic::parse(INPUT), &[GEAR]).unwrap(), 467835)
+    }
+
+    #[test]
+    fn rules() {
+        let s = Schematic::parse("1.2.3\n.*.*.\n4.5.6");
+        // each `*` touches four numbers, so the gear rule finds nothing
+        assert_eq!(part2(&s, &[GEAR]).unwrap(), 0);
+        let rule = Rule::parse("* 4 sum").unwrap();
+        assert_eq!(rule.combine, Combine::Sum);
+        assert_eq!(
+            part2(&s, &[rule]).unwrap(),
+            (1 + 2 + 4 + 5) + (2 + 3 + 5 + 6)
+        );
+        assert_eq!(
+            part2(&s, &[Rule::parse("* 4 product").unwrap()]).unwrap(),
+            40 + 180
+        );
+        assert_eq!(part2(&s, &[Rule::parse("# 4 sum").unwrap()]).unwrap(), 0);
+        assert!(Rule::parse("* 4 mean").is_err());
+        assert!(Rule::parse("* two sum").is_err());
+
+        let s = Schematic::parse(INPUT);
+        let rules = [GEAR, Rule::parse("# 1 max").unwrap()];
+        assert_eq!(part2(&s, &rules).unwrap(), 467835 + 633);
+
+        // 9999^6 is past u64::MAX
+        let s = Schematic::parse("9999.9999\n9999*9999\n9999.9999");
+        assert!(part2(&s, &[Rule::parse("* 6 product").unwrap()]).is_err());
+        assert_eq!(
+            part2(&s, &[Rule::parse("* 6 sum").unwrap()]).unwrap(),
+            6 * 9999
+        );
+    }
+
+    #[test]
+    fn report() {
+        let report = Schematic::parse(INPUT).report();
+        let lines = report.lines().collect_vec();
+        assert_eq!(lines[0], "467 at (0, 0): * at (1, 3)");
+        assert!(lines.contains(&"35 at (2, 2): * at (1, 3)"));
+        assert!(lines.contains(&"adjacent to nothing: 2"));
+        assert_eq!(lines[lines.len() - 2..], ["114 at (0, 5)", "58 at (5, 7)"]);
+    }
+
+    #[test]
+    fn touches() {
+        let part = Part {
+            num: 123,
+            row: 1,
+            col: 2,
+            len: 3,
+        };
+        for row in 0..=2 {
+            for col in 1..=5 {
+                assert!(part.touches((row, col)), "{row},{col}");
             }
-        });
-    });
-    println!("part2: {sum}")
+            assert!(!part.touches((row, 0)));
+            assert!(!part.touches((row, 6)));
+        }
+        assert!(!part.touches((3, 3)));
+
+        // at the edge of the grid nothing wraps around
+        let corner = Part {
+            num: 1,
+            row: 0,
+            col: 0,
+            len: 1,
+        };
+        assert!(corner.touches((1, 1)));
+        assert!(!corner.touches((0, 2)));
+    }
+
+    #[test]
+    fn index() {
+        let s = Schematic::parse("12.3\n*...\n..45");
+        assert_eq!(
+            s.parts.iter().map(|p| p.num).collect::<Vec<_>>(),
+            [12, 3, 45]
+        );
+        assert_eq!(s.parts_around((1, 0)), [0]);
+        assert_eq!(s.parts_around((1, 2)), [0, 1, 2]);
+        // the index agrees with the bounding box everywhere outside the numbers
+        for (pos, _) in s.grid.indexed_iter().filter(|&(p, _)| s.index[p].is_none()) {
+            let boxed = (0..s.parts.len())
+                .filter(|&i| s.parts[i].touches(pos))
+                .collect::<Vec<_>>();
+            assert_eq!(s.parts_around(pos), boxed, "{pos:?}");
+        }
+    }
 }
@@ -1,49 +1,244 @@
+use std::env;
+use std::ops::RangeInclusive;
+
+use anyhow::{ensure, Context, Result};
+use itertools::Itertools;
+
 use aoc_2023_rust::aoc;
+use aoc_2023_rust::parse::{self, ParseError};
 
 fn main() {
     let aoc = aoc().unwrap();
     let input = aoc.read_input(4).unwrap();
 
-    let wins = parse_cards(&input);
-    part1(&wins);
-    part2(&wins);
+    let cards = parse_cards(&input).unwrap();
+    if env::args().any(|a| a == "--explain") {
+        for card in &cards {
+            let matched = card.matched().iter().join(" ");
+            println!(
+                "Card {}: matched [{matched}], wins {:?}",
+                card.id,
+                card.won()
+            );
+        }
+    }
+    let wins = cards.iter().map(Card::matches).collect_vec();
+    println!("part1: {}", part1(&wins).unwrap());
+    println!("part2: {}", part2(&wins).unwrap());
 }
 
-fn parse_cards(input: &str) -> Vec<usize> {
-    input
-        .lines()
-        .map(|line| {
-            let (_, numbers) = line.split_once(": ").unwrap();
-            let (winning_nums, my_nums) = numbers.split_once(" | ").unwrap();
-            (parse_nums(winning_nums), parse_nums(my_nums))
+/// A set of numbers up to `u16::MAX`, one bit each.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct NumberSet(Vec<u64>);
+
+impl NumberSet {
+    /// Whether `n` was new.
+    fn insert(&mut self, n: u16) -> bool {
+        let (word, bit) = (n as usize / 64, 1 << (n % 64));
+        if word >= self.0.len() {
+            self.0.resize(word + 1, 0);
+        }
+        let new = self.0[word] & bit == 0;
+        self.0[word] |= bit;
+        new
+    }
+
+    fn intersection(&self, other: &Self) -> Self {
+        Self(self.0.iter().zip(&other.0).map(|(a, b)| a & b).collect())
+    }
+
+    fn len(&self) -> usize {
+        self.0.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.0.iter().enumerate().flat_map(|(i, &w)| {
+            (0..64)
+                .filter(move |b| w >> b & 1 == 1)
+                .map(move |b| i * 64 + b)
         })
-        .map(|(winning, my)| wins(&winning, &my))
-        .collect()
+    }
 }
 
-fn parse_nums(nums: &str) -> Vec<usize> {
-    nums.split_ascii_whitespace()
-        .filter_map(|n| n.parse().ok())
-        .collect()
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Card {
+    id: usize,
+    winning: NumberSet,
+    mine: NumberSet,
 }
 
-fn wins(winning: &[usize], my: &[usize]) -> usize {
-    my.iter().filter(|n| winning.contains(n)).count()
+impl Card {
+    fn parse(line: &str) -> parse::Result<Self> {
+        let (id, winning, mine): (usize, String, String) = parse::scan(line, "Card {}: {} | {}")?;
+        let set = |nums: &str, what: &str| {
+            let mut set = NumberSet::default();
+            for n in parse::numbers::<u16>(nums)? {
+                if !set.insert(n) {
+                    return Err(invalid(line, format!("{n} is listed twice in {what}")));
+                }
+            }
+            Ok(set)
+        };
+        Ok(Self {
+            id,
+            winning: set(&winning, "the winning numbers")?,
+            mine: set(&mine, "the numbers we have")?,
+        })
+    }
+
+    /// The numbers we have that are winning numbers.
+    fn matched(&self) -> NumberSet {
+        self.winning.intersection(&self.mine)
+    }
+
+    fn matches(&self) -> usize {
+        self.matched().len()
+    }
+
+    /// The ids of the cards this one wins a copy of.
+    fn won(&self) -> RangeInclusive<usize> {
+        self.id + 1..=self.id + self.matches()
+    }
 }
 
-fn part1(wins: &[usize]) {
-    let sum: usize = wins.iter().filter(|&&w| w > 0).map(|&w| 1 << (w - 1)).sum();
-    println!("part1: {sum}")
+fn invalid(line: &str, reason: String) -> ParseError {
+    ParseError::Invalid {
+        ty: "card",
+        input: line.to_owned(),
+        reason,
+    }
 }
 
-fn part2(wins: &[usize]) {
-    let mut cards = 0u32;
-    let mut stack: Vec<usize> = (0..wins.len()).collect();
-    while let Some(i) = stack.pop() {
-        cards += 1;
-        for j in 1..=wins[i] {
-            stack.push(i + j);
+/// Every card, which have to be numbered from 1 in order.
+fn parse_cards(input: &str) -> parse::Result<Vec<Card>> {
+    let mut cards: Vec<Card> = vec![];
+    for line in input.lines() {
+        let card = Card::parse(line)?;
+        if card.id != cards.len() + 1 {
+            return Err(invalid(line, format!("expected card {}", cards.len() + 1)));
         }
+        cards.push(card);
+    }
+    Ok(cards)
+}
+
+fn part1(wins: &[usize]) -> Result<u64> {
+    wins.iter()
+        .filter(|&&w| w > 0)
+        .try_fold(0u64, |sum, &w| {
+            let points = u32::try_from(w - 1)
+                .ok()
+                .and_then(|w| 1u64.checked_shl(w))?;
+            sum.checked_add(points)
+        })
+        .context("too many points")
+}
+
+fn part2(wins: &[usize]) -> Result<u64> {
+    copies(wins)?
+        .into_iter()
+        .try_fold(0u64, |sum, c| sum.checked_add(c))
+        .context("too many cards")
+}
+
+/// How many of each card we end up with. Every copy of a card adds one copy of each card it
+/// wins, so a running count of the extra copies still in effect (dropping them again where
+/// their run ends) settles every card in one pass. Winning cards past the last one is an error.
+fn copies(wins: &[usize]) -> Result<Vec<u64>> {
+    let mut copies = Vec::with_capacity(wins.len());
+    let mut ending = vec![0u64; wins.len() + 1];
+    let mut extra = 0u64;
+    for (i, &w) in wins.iter().enumerate() {
+        extra -= ending[i];
+        let count = extra.checked_add(1).context("too many copies")?;
+        copies.push(count);
+        if w == 0 {
+            continue;
+        }
+        let end = i + 1 + w;
+        ensure!(
+            end <= wins.len(),
+            "card {} wins {w} cards but only {} follow it",
+            i + 1,
+            wins.len() - i - 1
+        );
+        extra = extra.checked_add(count).context("too many copies")?;
+        ending[end] += count;
+    }
+    Ok(copies)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &str = "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11";
+
+    fn wins(input: &str) -> Vec<usize> {
+        parse_cards(input)
+            .unwrap()
+            .iter()
+            .map(Card::matches)
+            .collect()
+    }
+
+    #[test]
+    fn p1() {
+        assert_eq!(part1(&wins(INPUT)).unwrap(), 13);
+        assert_eq!(part1(&[64]).unwrap(), 1 << 63);
+        assert!(part1(&[65]).is_err());
+        assert!(part1(&[64, 64]).is_err());
+    }
+
+    #[test]
+    fn p2() {
+        let wins = wins(INPUT);
+        assert_eq!(copies(&wins).unwrap(), [1, 2, 4, 8, 14, 1]);
+        assert_eq!(part2(&wins).unwrap(), 30)
+    }
+
+    #[test]
+    fn cascade() {
+        // every card wins all the ones after it: the copies double all the way down
+        let mut wins = (0..64).rev().collect::<Vec<_>>();
+        let copies = copies(&wins).unwrap();
+        assert_eq!(copies[63], 1 << 63);
+        assert_eq!(part2(&wins).unwrap(), u64::MAX);
+        wins.insert(0, 64);
+        assert!(part2(&wins).is_err());
+
+        assert!(super::copies(&[0, 2, 0]).is_err());
+        assert_eq!(super::copies(&[0, 1, 0]).unwrap(), [1, 1, 2]);
+        assert_eq!(part2(&[]).unwrap(), 0);
+    }
+
+    #[test]
+    fn cards() {
+        let cards = parse_cards(INPUT).unwrap();
+        assert_eq!(cards[0].matched().iter().collect_vec(), [17, 48, 83, 86]);
+        assert_eq!(cards[0].won(), 2..=5);
+        assert_eq!(cards[2].matched().iter().collect_vec(), [1, 21]);
+        assert!(cards[5].won().is_empty());
+
+        let card = Card::parse("Card 7: 1 100 200 | 200 3 100").unwrap();
+        assert_eq!(card.matched().iter().collect_vec(), [100, 200]);
+
+        assert!(Card::parse("Card 1: 41 48 41 | 83").is_err());
+        assert!(Card::parse("Card 1: 41 48 | 83 83").is_err());
+        assert!(Card::parse("Card 1: 41 4x | 83").is_err());
+        assert!(matches!(
+            Card::parse("Card 1: 41 | 18446744073709551615"),
+            Err(ParseError::Invalid { ty: "u16", .. })
+        ));
+        let card = Card::parse("Card 1: 65535 0 | 65535 1").unwrap();
+        assert_eq!(card.matched().iter().collect_vec(), [65535]);
+        assert!(Card::parse("Card 1: 41 48 83").is_err());
+        assert!(parse_cards("Card 1: 1 | 2\nCard 3: 1 | 2").is_err());
+        assert!(parse_cards("Card 2: 1 | 2").is_err());
     }
-    println!("part2: {cards}")
 }
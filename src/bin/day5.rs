@@ -1,27 +1,61 @@
 use std::cmp::{max, min};
-use std::str::Lines;
+use std::env;
+use std::fmt::{Display, Formatter};
 
+use anyhow::{bail, ensure, Result};
 use itertools::Itertools;
+use petgraph::algo::astar;
+use petgraph::prelude::DiGraphMap;
 
 use aoc_2023_rust::aoc;
+use aoc_2023_rust::interval::{IntervalSet, PiecewiseMap, Segment};
+use aoc_2023_rust::parse::{self, ParseError};
 
 fn main() {
     let aoc = aoc().unwrap();
     let input = aoc.read_input(5).unwrap();
 
-    let mut lines = input.lines();
-    let seeds = lines.next().unwrap();
-    lines.next();
-    let maps = parse_maps(&mut lines);
-
-    let seeds1 = parse_seeds1(seeds);
-    part1(seeds1, &maps);
+    let (seeds, almanac) = parse(&input).unwrap();
+    let converter = almanac.converter("seed", "location").unwrap();
+    if env::args().any(|a| a == "--inspect") {
+        println!("segments: {}", converter.segments().len());
+        println!("breakpoints: {:?}", converter.breakpoints());
+    }
+    // `--convert FROM TO VALUE`, e.g. `--convert location seed 46`
+    if let Some((from, to, value)) = env::args()
+        .skip_while(|a| a != "--convert")
+        .skip(1)
+        .next_tuple()
+    {
+        let value = value.parse().unwrap();
+        println!(
+            "{from} {value} is {to} {}",
+            almanac.convert(&from, &to, value).unwrap()
+        );
+    }
 
+    println!("part1: {}", part1(&parse_seeds1(seeds), &converter));
     let seeds2 = parse_seeds2(seeds);
-    part2(seeds2, &maps);
+    // `--stepwise` maps the seed ranges through one category at a time instead, `--reverse`
+    // searches upwards from location 0
+    let part2 = if env::args().any(|a| a == "--stepwise") {
+        part2_stepwise(seeds2, &almanac)
+    } else if env::args().any(|a| a == "--reverse") {
+        let (location, seed) = part2_reverse(&seeds2, &almanac).unwrap();
+        println!("lowest location {location} is for seed {seed}");
+        location
+    } else {
+        part2(&seeds2, &almanac)
+    };
+    println!("part2: {part2}");
 }
 
-#[derive(Debug)]
+fn parse(input: &str) -> parse::Result<(&str, Almanac<'_>)> {
+    let (seeds, maps) = input.split_once('\n').unwrap_or((input, ""));
+    Ok((seeds, Almanac::parse(maps)?))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 struct Mapping {
     src: u64,
     dst: u64,
@@ -29,14 +63,6 @@ struct Mapping {
 }
 
 impl Mapping {
-    fn apply1(&self, seed: u64) -> Option<u64> {
-        if (self.src..self.src + self.len).contains(&seed) {
-            Some(self.dst + seed - self.src)
-        } else {
-            None
-        }
-    }
-
     fn apply2(&self, seed: &Seed) -> Option<(Seed, Vec<Seed>)> {
         if !(seed.i..seed.i + seed.len).contains(&self.src)
             && !(self.src..self.src + self.len).contains(&seed.i)
@@ -69,27 +95,178 @@ impl Mapping {
         Some((mapped, rem))
     }
 
-    fn parse(line: &str) -> Self {
-        let [dst, src, len] = line
-            .split_ascii_whitespace()
-            .map(|s| s.parse().unwrap())
-            .collect::<Vec<u64>>()
-            .try_into()
-            .unwrap();
-        Self { src, dst, len }
+    fn segment(&self) -> Segment<u64> {
+        Segment {
+            src: self.src..self.src + self.len,
+            dst: self.dst,
+        }
+    }
+
+    fn inverse(&self) -> Self {
+        Self {
+            src: self.dst,
+            dst: self.src,
+            len: self.len,
+        }
+    }
+
+    fn parse(line: &str) -> parse::Result<Self> {
+        let (dst, src, len): (u64, u64, u64) = parse::fields(line)?;
+        if src.checked_add(len).and(dst.checked_add(len)).is_none() {
+            return Err(ParseError::Invalid {
+                ty: "mapping",
+                input: line.to_owned(),
+                reason: "range ends past u64::MAX".to_owned(),
+            });
+        }
+        Ok(Self { src, dst, len })
+    }
+}
+
+impl Display for Mapping {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} {}", self.dst, self.src, self.len)
+    }
+}
+
+/// One map of the almanac, both as listed and as a function.
+#[derive(Debug, Clone)]
+struct Conversion {
+    mappings: Vec<Mapping>,
+    map: PiecewiseMap<u64>,
+}
+
+impl Conversion {
+    fn new(mappings: Vec<Mapping>) -> Option<Self> {
+        let map = PiecewiseMap::new(mappings.iter().map(Mapping::segment))?;
+        Some(Self { mappings, map })
+    }
+
+    /// The conversion back, if no two values convert to the same one.
+    fn inverse(&self) -> Option<Self> {
+        Some(Self {
+            mappings: self.mappings.iter().map(Mapping::inverse).collect(),
+            map: self.map.invert()?,
+        })
     }
 }
 
-fn parse_maps(lines: &mut Lines) -> Vec<Vec<Mapping>> {
-    let mut maps = vec![];
-    while let Some(_) = lines.next() {
-        let mappings = lines
-            .take_while(|l| !l.is_empty())
-            .map(Mapping::parse)
-            .collect();
-        maps.push(mappings);
+/// The categories and the conversions between them, including the inverse of every map that
+/// has one.
+#[derive(Debug, Clone)]
+struct Almanac<'a> {
+    graph: DiGraphMap<&'a str, Conversion>,
+    /// The `(from, to)` of the maps that have no inverse.
+    one_way: Vec<(&'a str, &'a str)>,
+}
+
+impl<'a> Almanac<'a> {
+    fn parse(input: &'a str) -> parse::Result<Self> {
+        let mut sections: Vec<(&str, Vec<&str>)> = vec![];
+        for line in input.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            match sections.last_mut() {
+                _ if line.ends_with("map:") => sections.push((line, vec![])),
+                Some((_, lines)) => lines.push(line),
+                None => return Err(invalid(line, "mapping before any map header".to_owned())),
+            }
+        }
+        let mut graph = DiGraphMap::new();
+        for (header, lines) in sections {
+            let (from, rest) = parse::split_once(header, "-to-")?;
+            let (to, _) = parse::split_once(rest, " map:")?;
+            let mappings = lines
+                .into_iter()
+                .map(Mapping::parse)
+                .collect::<parse::Result<Vec<_>>>()?;
+            // `Mapping::parse` rejects ranges ending past `u64::MAX`, so `src + len` can't overflow
+            for (a, b) in mappings.iter().sorted_by_key(|m| m.src).tuple_windows() {
+                if a.src + a.len > b.src {
+                    return Err(invalid(header, format!("\"{a}\" overlaps \"{b}\"")));
+                }
+            }
+            let conversion = Conversion::new(mappings).expect("no overlaps");
+            if graph.add_edge(from, to, conversion).is_some() {
+                return Err(invalid(header, "listed twice".to_owned()));
+            }
+        }
+        let (inverses, one_way): (Vec<_>, Vec<_>) = graph
+            .all_edges()
+            .filter(|&(from, to, _)| !graph.contains_edge(to, from))
+            .map(|(from, to, c)| c.inverse().map(|c| (to, from, c)).ok_or((from, to)))
+            .partition_result();
+        for (from, to, c) in inverses {
+            graph.add_edge(from, to, c);
+        }
+        Ok(Self { graph, one_way })
+    }
+
+    /// The maps converting `from` to `to`, fewest first.
+    fn path(&self, from: &str, to: &str) -> Result<Vec<&Conversion>> {
+        for category in [from, to] {
+            ensure!(
+                self.graph.contains_node(category),
+                "unknown category {category:?}"
+            );
+        }
+        let (from, to) = (self.node(from), self.node(to));
+        let Some((_, path)) = astar(&self.graph, from, |n| n == to, |_| 1, |_| 0) else {
+            // had the one-way maps been invertible, would there be a way?
+            let reversed = self.one_way.iter().map(|&(a, b)| (b, a));
+            let graph: DiGraphMap<&str, ()> = self
+                .graph
+                .all_edges()
+                .map(|(a, b, _)| (a, b))
+                .chain(reversed)
+                .collect();
+            if let Some((_, path)) = astar(&graph, from, |n| n == to, |_| 1, |_| 0) {
+                let (a, b) = path
+                    .into_iter()
+                    .tuple_windows()
+                    .find(|&(a, b)| !self.graph.contains_edge(a, b))
+                    .unwrap();
+                bail!("no conversion from {from} to {to}: map {b}-to-{a} is not invertible");
+            }
+            bail!("no conversion from {from} to {to}");
+        };
+        Ok(path
+            .into_iter()
+            .tuple_windows()
+            .map(|(a, b)| &self.graph[(a, b)])
+            .collect())
+    }
+
+    fn node(&self, category: &str) -> &'a str {
+        self.graph.nodes().find(|&n| n == category).unwrap()
+    }
+
+    /// Everything from `from` to `to` as a single map.
+    fn converter(&self, from: &str, to: &str) -> Result<PiecewiseMap<u64>> {
+        Ok(self
+            .path(from, to)?
+            .into_iter()
+            .fold(PiecewiseMap::identity(), |acc, c| acc.then(&c.map)))
+    }
+
+    fn convert(&self, from: &str, to: &str, value: u64) -> Result<u64> {
+        Ok(self.converter(from, to)?.apply(value))
+    }
+
+    fn convert_set(
+        &self,
+        from: &str,
+        to: &str,
+        set: &IntervalSet<u64>,
+    ) -> Result<IntervalSet<u64>> {
+        Ok(self.converter(from, to)?.apply_set(set))
+    }
+}
+
+fn invalid(input: &str, reason: String) -> ParseError {
+    ParseError::Invalid {
+        ty: "almanac",
+        input: input.to_owned(),
+        reason,
     }
-    maps
 }
 
 fn parse_seeds1(line: &str) -> Vec<u64> {
@@ -100,22 +277,8 @@ fn parse_seeds1(line: &str) -> Vec<u64> {
         .collect()
 }
 
-fn part1<I>(mut seeds: Vec<u64>, maps: &[I])
-where
-    I: AsRef<[Mapping]>,
-{
-    for map in maps {
-        for seed in seeds.iter_mut() {
-            for mapping in map.as_ref() {
-                if let Some(dst) = mapping.apply1(*seed) {
-                    *seed = dst;
-                    break;
-                }
-            }
-        }
-    }
-    seeds.sort();
-    println!("part1: {}", seeds.first().unwrap())
+fn part1(seeds: &[u64], almanac: &PiecewiseMap<u64>) -> u64 {
+    seeds.iter().map(|&s| almanac.apply(s)).min().unwrap()
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
@@ -134,15 +297,41 @@ fn parse_seeds2(line: &str) -> Vec<Seed> {
         .collect()
 }
 
-fn part2<I>(mut seeds: Vec<Seed>, maps: &[I])
-where
-    I: AsRef<[Mapping]>,
-{
-    for map in maps {
-        seeds = do_map(seeds, map.as_ref());
+fn part2(seeds: &[Seed], almanac: &Almanac) -> u64 {
+    let seeds: IntervalSet<u64> = seeds.iter().map(|s| s.i..s.i + s.len).collect();
+    let locations = almanac.convert_set("seed", "location", &seeds).unwrap();
+    locations.min().unwrap()
+}
+
+fn part2_stepwise(mut seeds: Vec<Seed>, almanac: &Almanac) -> u64 {
+    for conversion in almanac.path("seed", "location").unwrap() {
+        seeds = do_map(seeds, &conversion.mappings);
+    }
+    seeds.iter().map(|s| s.i).min().unwrap()
+}
+
+/// The lowest location any seed reaches and that seed, found by going through the locations in
+/// order, each linear piece of the location to seed map at once, until one is a seed.
+fn part2_reverse(seeds: &[Seed], almanac: &Almanac) -> Result<(u64, u64)> {
+    let seeds: IntervalSet<u64> = seeds.iter().map(|s| s.i..s.i + s.len).collect();
+    let inverse = almanac.converter("location", "seed")?;
+    let breakpoints = inverse.breakpoints();
+    let mut location = 0;
+    loop {
+        let i = breakpoints.partition_point(|&b| b <= location);
+        let end = breakpoints.get(i).copied().unwrap_or(u64::MAX);
+        let start = inverse.apply(location);
+        let len = end - location;
+        let j = seeds.ranges().partition_point(|r| r.end <= start);
+        match seeds.ranges().get(j) {
+            Some(r) if r.start.saturating_sub(start) < len => {
+                let skip = r.start.saturating_sub(start);
+                return Ok((location + skip, start + skip));
+            }
+            _ if end == u64::MAX => bail!("no seed reaches any location"),
+            _ => location = end,
+        }
     }
-    seeds.sort_by_key(|s| s.i);
-    println!("part2: {}", seeds.first().unwrap().i)
 }
 
 fn do_map(mut seeds: Vec<Seed>, map: &[Mapping]) -> Vec<Seed> {
@@ -163,6 +352,182 @@ fn do_map(mut seeds: Vec<Seed>, map: &[Mapping]) -> Vec<Seed> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use aoc_2023_rust::rng::XorShift;
+
+    const INPUT: &str = "seeds: 79 14 55 13
+
+seed-to-soil map:
+50 98 2
+52 50 48
+
+soil-to-fertilizer map:
+0 15 37
+37 52 2
+39 0 15
+
+fertilizer-to-water map:
+49 53 8
+0 11 42
+42 0 7
+57 7 4
+
+water-to-light map:
+88 18 7
+18 25 70
+
+light-to-temperature map:
+45 77 23
+81 45 19
+68 64 13
+
+temperature-to-humidity map:
+0 69 1
+1 0 69
+
+humidity-to-location map:
+60 56 37
+56 93 4
+";
+
+    #[test]
+    fn p1() {
+        let (seeds, almanac) = parse(INPUT).unwrap();
+        let converter = almanac.converter("seed", "location").unwrap();
+        assert_eq!(part1(&parse_seeds1(seeds), &converter), 35)
+    }
+
+    #[test]
+    fn p2() {
+        let (seeds, almanac) = parse(INPUT).unwrap();
+        let seeds = parse_seeds2(seeds);
+        assert_eq!(part2(&seeds, &almanac), 46);
+        assert_eq!(part2_reverse(&seeds, &almanac).unwrap(), (46, 82));
+        assert_eq!(part2_stepwise(seeds, &almanac), 46);
+    }
+
+    #[test]
+    fn reverse() {
+        let mut rng = XorShift::new(0x2023_1205);
+        let mut next = |n: u64| rng.below(n);
+        let categories = ["seed", "soil", "water", "light", "location"];
+        for _ in 0..200 {
+            // each map shuffles the pieces of 0..100 around, so it can be inverted
+            let mut input = "seeds:\n".to_owned();
+            for (from, to) in categories.iter().tuple_windows() {
+                input += &format!("\n{from}-to-{to} map:\n");
+                let mut cuts = (0..next(6)).map(|_| next(100)).collect_vec();
+                cuts.extend([0, 100]);
+                cuts.sort();
+                cuts.dedup();
+                let mut pieces = cuts.iter().tuple_windows().collect_vec();
+                for i in (1..pieces.len()).rev() {
+                    pieces.swap(i, next(i as u64 + 1) as usize);
+                }
+                let mut dst = 0;
+                for (&src, &end) in pieces {
+                    input += &format!("{dst} {src} {}\n", end - src);
+                    dst += end - src;
+                }
+            }
+            let (_, almanac) = parse(&input).unwrap();
+            let seeds = (0..next(4) + 1)
+                .map(|_| Seed {
+                    i: next(120),
+                    len: next(20) + 1,
+                })
+                .collect_vec();
+            let (location, seed) = part2_reverse(&seeds, &almanac).unwrap();
+            assert_eq!(location, part2_stepwise(seeds.clone(), &almanac), "{input}");
+            assert_eq!(almanac.convert("seed", "location", seed).unwrap(), location);
+            assert!(seeds.iter().any(|s| (s.i..s.i + s.len).contains(&seed)));
+        }
+        let (_, almanac) = parse(INPUT).unwrap();
+        assert!(part2_reverse(&[], &almanac).is_err());
+    }
+
+    #[test]
+    fn composed() {
+        let (_, almanac) = parse(INPUT).unwrap();
+        let converter = almanac.converter("seed", "location").unwrap();
+        let maps = almanac.path("seed", "location").unwrap();
+        assert_eq!(maps.len(), 7);
+        for seed in 0..120 {
+            let location = maps.iter().fold(seed, |x, c| {
+                c.mappings
+                    .iter()
+                    .find(|m| (m.src..m.src + m.len).contains(&x))
+                    .map_or(x, |m| m.dst + x - m.src)
+            });
+            assert_eq!(converter.apply(seed), location, "seed {seed}");
+        }
+        let breakpoints = converter.breakpoints();
+        assert!(breakpoints.windows(2).all(|w| w[0] < w[1]));
+        assert!(converter.segments().len() < breakpoints.len());
+        assert_eq!(
+            converter.apply(breakpoints[breakpoints.len() - 1]),
+            breakpoints[breakpoints.len() - 1]
+        );
+    }
+
+    #[test]
+    fn categories() {
+        let (_, almanac) = parse(INPUT).unwrap();
+        assert_eq!(almanac.convert("seed", "soil", 79).unwrap(), 81);
+        assert_eq!(almanac.convert("soil", "water", 81).unwrap(), 81);
+        assert_eq!(almanac.convert("location", "seed", 46).unwrap(), 82);
+        assert_eq!(almanac.convert("humidity", "soil", 46).unwrap(), 84);
+        let seeds = IntervalSet::from(79..93);
+        let locations = almanac.convert_set("seed", "location", &seeds).unwrap();
+        assert_eq!(
+            almanac.convert_set("location", "seed", &locations).unwrap(),
+            seeds
+        );
+        assert!(almanac.convert("seed", "colour", 1).is_err());
+
+        // only one way each, since 0 and 5 (and 1 and 5) both end up at 5
+        let (_, lossy) = parse("seeds: 1\n\na-to-b map:\n5 0 1\n\nc-to-b map:\n5 1 1").unwrap();
+        assert_eq!(lossy.convert("a", "b", 0).unwrap(), 5);
+        let err = lossy.convert("b", "a", 5).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "no conversion from b to a: map a-to-b is not invertible"
+        );
+        let err = lossy.convert("a", "c", 0).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "no conversion from a to c: map c-to-b is not invertible"
+        );
+        assert_eq!(
+            lossy.convert("a", "x", 0).unwrap_err().to_string(),
+            "unknown category \"x\""
+        );
+        let (_, apart) = parse("seeds: 1\n\na-to-b map:\n0 0 1\n\nc-to-d map:\n0 0 1").unwrap();
+        assert_eq!(
+            apart.convert("a", "d", 0).unwrap_err().to_string(),
+            "no conversion from a to d"
+        );
+        assert_eq!(lossy.convert("c", "b", 1).unwrap(), 5);
+
+        let overlapping = parse("seeds: 1\n\na-to-b map:\n0 10 5\n20 12 5");
+        assert!(
+            matches!(overlapping, Err(ParseError::Invalid { reason, .. }) if reason == "\"0 10 5\" overlaps \"20 12 5\"")
+        );
+        assert!(parse("seeds: 1\n\na-to-b map:\n0 10 5\na-to-b map:\n1 1 1").is_err());
+        assert!(parse("seeds: 1\n\na-to-b map:\n0 10").is_err());
+        // ranges reaching past u64::MAX can neither overlap-check nor convert
+        let max = u64::MAX;
+        let input = format!("seeds: 1\n\na-to-b map:\n0 {max} 2");
+        let past_end = parse(&input);
+        assert!(
+            matches!(past_end, Err(ParseError::Invalid { ty: "mapping", .. })),
+            "{past_end:?}"
+        );
+        assert!(parse(&format!("seeds: 1\n\na-to-b map:\n{max} 0 2")).is_err());
+        let input = format!("seeds: 1\n\na-to-b map:\n0 {} 1", max - 1);
+        let (_, edge) = parse(&input).unwrap();
+        assert_eq!(edge.convert("a", "b", max - 1).unwrap(), 0);
+        assert!(parse("seeds: 1\n\n0 10 1").is_err());
+    }
 
     #[test]
     fn mapping_apply() {
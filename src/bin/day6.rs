@@ -5,30 +5,25 @@ use aoc_2023_rust::aoc;
 fn main() {
     let aoc = aoc().unwrap();
     let input = aoc.read_input(6).unwrap();
-    part1(&input);
-    part2(&input);
+    println!("part1: {}", part1(&input));
+    println!("part2: {}", part2(&input));
 }
 
-fn part1(input: &str) {
+fn part1(input: &str) -> u64 {
     let (t, d) = input
         .lines()
         .map(|l| l.split_once(':').unwrap().1)
         .map(|l| {
             l.split_ascii_whitespace()
-                .map(|n| n.parse::<u32>().unwrap())
+                .map(|n| n.parse::<u64>().unwrap())
         })
         .collect_tuple()
         .unwrap();
 
-    let ans = t
-        .zip(d)
-        .map(|(t, d)| solve(t as f64, d as f64))
-        .reduce(|a, b| a * b)
-        .unwrap();
-    println!("part1: {}", ans)
+    t.zip(d).map(|(t, d)| solve(t, d)).product()
 }
 
-fn part2(input: &str) {
+fn part2(input: &str) -> u64 {
     let (t, d) = input
         .lines()
         .map(|l| l.split_once(':').unwrap().1)
@@ -36,22 +31,75 @@ fn part2(input: &str) {
         .collect_tuple()
         .unwrap();
 
-    println!("part2: {}", solve(t as f64, d as f64))
+    solve(t, d)
 }
 
-fn solve(t: f64, d: f64) -> u64 {
-    let x1 = ((t - (t * t - 4f64 * d).sqrt()) / 2f64).ceil() as u64;
-    let x2 = ((t + (t * t - 4f64 * d).sqrt()) / 2f64).floor() as u64;
-    x2 - x1 + 1
+/// How many ways of holding the button for `h` of the `t` milliseconds travel further than `d`,
+/// i.e. `h * (t - h) > d`. Integer roots of the quadratic only tie the record, so they don't count.
+fn solve(t: u64, d: u64) -> u64 {
+    let (t, d) = (t as u128, d as u128);
+    let beats = |h: u128| h * (t - h) > d;
+    if t * t <= 4 * d {
+        return 0;
+    }
+    // the smaller root is (t - sqrt(t² - 4d)) / 2; isqrt rounds down, so start at or below it
+    let mut lo = (t - (t * t - 4 * d).isqrt()) / 2;
+    while lo > 0 && beats(lo - 1) {
+        lo -= 1;
+    }
+    while lo <= t / 2 && !beats(lo) {
+        lo += 1;
+    }
+    if lo > t / 2 {
+        return 0;
+    }
+    // the wins are symmetric around t / 2
+    (t - 2 * lo + 1) as u64
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    const INPUT: &str = "Time:      7  15   30
+Distance:  9  40  200";
+
     #[test]
     fn test_solve1() {
-        assert_eq!(solve(7f64, 9f64), 4);
-        assert_eq!(solve(15f64, 40f64), 8);
+        assert_eq!(solve(7, 9), 4);
+        assert_eq!(solve(15, 40), 8);
+    }
+
+    #[test]
+    fn parts() {
+        assert_eq!(part1(INPUT), 288);
+        assert_eq!(part2(INPUT), 71503);
+    }
+
+    #[test]
+    fn ties() {
+        // 10 and 20 only match the record
+        assert_eq!(solve(30, 200), 9);
+        assert_eq!(solve(4, 4), 0);
+        assert_eq!(solve(3, 2), 0);
+        assert_eq!(solve(0, 0), 0);
+        for t in 0..60 {
+            for d in 0..t * t / 4 + 3 {
+                let brute = (0..=t).filter(|h| h * (t - h) > d).count() as u64;
+                assert_eq!(solve(t, d), brute, "t={t} d={d}");
+            }
+        }
+    }
+
+    #[test]
+    fn large() {
+        // roots at exactly k - m and k + m, with t² far past the integers f64 holds exactly
+        let (k, m) = (4_000_000_000u64, 1_000_000);
+        assert_eq!(solve(2 * k, k * k - m * m), 2 * m - 1);
+        assert_eq!(solve(2 * k, k * k - m * m - 1), 2 * m + 1);
+        assert_eq!(solve(2 * k, k * k - 1), 1);
+        assert_eq!(solve(2 * k, k * k), 0);
+        assert_eq!(solve(u64::MAX, 0), u64::MAX - 1);
+        assert_eq!(solve(u64::MAX, u64::MAX), u64::MAX - 3);
     }
 }
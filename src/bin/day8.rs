@@ -1,10 +1,13 @@
 use std::collections::HashMap;
+use std::env;
 use std::str::Lines;
 
 use itertools::Itertools;
-use num::integer::lcm;
+use petgraph::prelude::DiGraphMap;
 
-use aoc_2023_rust::aoc;
+use aoc_2023_rust::graph::{self, Highlight};
+use aoc_2023_rust::numtheory::{self, Hits};
+use aoc_2023_rust::{aoc, parse};
 
 fn main() {
     let aoc = aoc().unwrap();
@@ -13,8 +16,12 @@ fn main() {
     let moves = lines.next().unwrap().chars().collect_vec();
     lines.next();
     let locs = Loc::parse(lines);
+    if env::args().any(|a| a == "--dot") {
+        print!("{}", dot(&moves, &locs));
+        return;
+    }
     part1(&moves, &locs);
-    part2(&moves, &locs);
+    println!("part2: {}", part2(&moves, &locs));
 }
 
 #[derive(Debug)]
@@ -24,10 +31,8 @@ impl Loc {
     fn parse(lines: Lines) -> HashMap<String, Loc> {
         lines
             .map(|line| {
-                (
-                    line[0..3].to_string(),
-                    Loc(line[7..10].to_owned(), line[12..15].to_owned()),
-                )
+                let (from, l, r) = parse::scan(line, "{} = ({}, {})").unwrap();
+                (from, Loc(l, r))
             })
             .collect()
     }
@@ -42,42 +47,111 @@ impl Loc {
 }
 
 fn part1(moves: &[char], locs: &HashMap<String, Loc>) {
-    let mut mv_cnt = 0usize;
-    let mut cur = locs.get("AAA").unwrap();
-    'main: loop {
-        for d in moves {
-            mv_cnt += 1;
-            let dst = cur.nav(*d);
-            if dst == "ZZZ" {
-                break 'main;
-            }
-            cur = locs.get(dst).unwrap();
+    println!("part1: {}", walk(moves, locs).len() - 1)
+}
+
+/// The nodes visited from `AAA` to `ZZZ`.
+fn walk<'a>(moves: &[char], locs: &'a HashMap<String, Loc>) -> Vec<&'a str> {
+    let mut path = vec!["AAA"];
+    for d in moves.iter().cycle() {
+        let cur = *path.last().unwrap();
+        if cur == "ZZZ" {
+            break;
         }
+        path.push(locs[cur].nav(*d));
+    }
+    path
+}
+
+fn network(locs: &HashMap<String, Loc>) -> DiGraphMap<&str, &'static str> {
+    let mut g = DiGraphMap::new();
+    for (from, Loc(l, r)) in locs {
+        if l == r {
+            g.add_edge(from.as_str(), l.as_str(), "LR");
+        } else {
+            g.add_edge(from.as_str(), l.as_str(), "L");
+            g.add_edge(from.as_str(), r.as_str(), "R");
+        }
+    }
+    g
+}
+
+/// The network as Graphviz, with the walk from `AAA` and the ghosts' start and end nodes marked.
+fn dot(moves: &[char], locs: &HashMap<String, Loc>) -> String {
+    let g = network(locs);
+    let mut highlight =
+        Highlight::default().nodes(g.nodes().filter(|n| n.ends_with('A') || n.ends_with('Z')));
+    if locs.contains_key("AAA") {
+        highlight = highlight.path(&walk(moves, locs));
     }
-    println!("part1: {}", mv_cnt)
+    let summary = format!(
+        "// {} nodes in {} components\n",
+        g.node_count(),
+        graph::components(&g).len()
+    );
+    summary + &graph::to_dot(&g, &highlight, |l| Some(l.to_string()))
 }
 
-fn part2(moves: &[char], locs: &HashMap<String, Loc>) {
-    let src = locs.keys().filter(|k| k.ends_with('A')).collect_vec();
-    let cnt = src
-        .iter()
-        .map(|&s| part2_len(locs.get(s).unwrap(), moves, locs))
+fn part2(moves: &[char], locs: &HashMap<String, Loc>) -> u64 {
+    let hits = locs
+        .keys()
+        .filter(|k| k.ends_with('A'))
+        .map(|s| ghost_hits(s, moves, locs))
         .collect_vec();
-    let ans = cnt.into_iter().reduce(lcm).unwrap();
-    println!("part2: {}", ans)
+    numtheory::first_common(&hits).unwrap()
 }
 
-fn part2_len<'a>(mut cur: &'a Loc, moves: &[char], locs: &'a HashMap<String, Loc>) -> usize {
-    let mut mv_cnt = 0usize;
-    'main: loop {
-        for d in moves {
-            mv_cnt += 1;
-            let dst = cur.nav(*d);
-            if dst.ends_with('Z') {
-                break 'main;
-            }
-            cur = locs.get(dst).unwrap();
-        }
+/// The move counts at which a ghost starting at `start` stands on a `..Z` node.
+fn ghost_hits(start: &str, moves: &[char], locs: &HashMap<String, Loc>) -> Hits {
+    Hits::find(
+        (start, 0),
+        |&(loc, i)| (locs[loc].nav(moves[i]), (i + 1) % moves.len()),
+        |(loc, _)| loc.ends_with('Z'),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn p1() {
+        let input = "LLR
+
+AAA = (BBB, BBB)
+BBB = (AAA, ZZZ)
+ZZZ = (ZZZ, ZZZ)";
+        let mut lines = input.lines();
+        let moves = lines.next().unwrap().chars().collect_vec();
+        lines.next();
+        let locs = Loc::parse(lines);
+        assert_eq!(
+            walk(&moves, &locs),
+            ["AAA", "BBB", "AAA", "BBB", "AAA", "BBB", "ZZZ"]
+        );
+        let dot = dot(&moves, &locs);
+        assert!(dot.starts_with("// 3 nodes in 1 components\ndigraph {"));
+        assert!(dot.contains("\"BBB\" -> \"ZZZ\" [label=\"R\", color=red"));
+    }
+
+    #[test]
+    fn p2() {
+        let input = "LR
+
+11A = (11B, XXX)
+11B = (XXX, 11Z)
+11Z = (11B, XXX)
+22A = (22B, XXX)
+22B = (22C, 22C)
+22C = (22Z, 22Z)
+22Z = (22B, 22B)
+XXX = (XXX, XXX)";
+        let mut lines = input.lines();
+        let moves = lines.next().unwrap().chars().collect_vec();
+        lines.next();
+        let locs = Loc::parse(lines);
+        assert_eq!(part2(&moves, &locs), 6);
+        // the ghosts share the dead end XXX, so the network is one piece
+        assert_eq!(graph::components(&network(&locs)).len(), 1);
     }
-    mv_cnt
 }
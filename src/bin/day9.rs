@@ -1,52 +1,35 @@
 use itertools::Itertools;
-use num::Zero;
 
-use aoc_2023_rust::aoc;
+use aoc_2023_rust::poly::Differences;
+use aoc_2023_rust::{aoc, parse};
 
 fn main() {
     let aoc = aoc().unwrap();
     let input = aoc.read_input(9).unwrap();
     let nums = input
         .lines()
-        .map(|l| l.split_ascii_whitespace())
-        .map(|nums| nums.map(|n| n.parse::<i64>().unwrap()).collect_vec())
+        .map(|l| parse::numbers(l).unwrap())
         .collect_vec();
     part1(&nums);
     part2(&nums);
 }
 
 fn part1(nums: &[Vec<i64>]) {
-    let sum = nums.iter().cloned().map(extrapolate1).sum::<i64>();
+    let sum = nums
+        .iter()
+        .map(|n| extrapolate(n, n.len() as i128))
+        .sum::<i128>();
     println!("part 1: {}", sum);
 }
 
-fn extrapolate1(mut nums: Vec<i64>) -> i64 {
-    let mut lasts = vec![];
-    while nums.iter().any(|n| !n.is_zero()) {
-        for i in 0..nums.len() - 1 {
-            nums[i] = nums[i + 1] - nums[i];
-        }
-        lasts.push(nums.pop().unwrap());
-    }
-    lasts.iter().sum()
-}
-
 fn part2(nums: &[Vec<i64>]) {
-    let sum = nums.iter().cloned().map(extrapolate2).sum::<i64>();
+    let sum = nums.iter().map(|n| extrapolate(n, -1)).sum::<i128>();
     println!("part 2: {}", sum);
 }
 
-fn extrapolate2(mut nums: Vec<i64>) -> i64 {
-    let mut firsts = vec![];
-    while nums.iter().any(|n| !n.is_zero()) {
-        firsts.push(nums[0]);
-        for i in 0..nums.len() - 1 {
-            nums[i] = nums[i + 1] - nums[i];
-        }
-        nums.pop().unwrap();
-    }
-    firsts.reverse();
-    firsts.iter().fold(0, |acc, n| n - acc)
+fn extrapolate(nums: &[i64], at: i128) -> i128 {
+    let nums = nums.iter().map(|&n| n as i128).collect_vec();
+    Differences::new(&nums, None).unwrap().at(at).unwrap()
 }
 
 #[cfg(test)]
@@ -55,6 +38,6 @@ mod tests {
 
     #[test]
     fn part2() {
-        assert_eq!(extrapolate2(vec![10, 13, 16, 21, 30, 45]), 5);
+        assert_eq!(extrapolate(&[10, 13, 16, 21, 30, 45], -1), 5);
     }
 }
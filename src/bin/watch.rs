@@ -0,0 +1,82 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs::metadata;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::thread::sleep;
+use std::time::{Duration, SystemTime};
+
+use anyhow::{bail, Context, Result};
+
+use aoc_2023_rust::answers::Answers;
+use aoc_2023_rust::{aoc, AoC, INPUT_ENV};
+
+fn main() -> Result<()> {
+    let day: u8 = env::args()
+        .nth(1)
+        .context("usage: watch <day>")?
+        .parse()
+        .context("day must be a number")?;
+    let aoc = aoc()?;
+    let mut last = None;
+    let mut prev = HashMap::new();
+    loop {
+        let files = watched(&aoc, day)?;
+        let stamp = files.iter().map(|f| modified(f)).collect::<Vec<_>>();
+        if last.as_ref() != Some(&stamp) {
+            last = Some(stamp);
+            if let Err(e) = rerun(&aoc, day, &mut prev) {
+                println!("{e:#}");
+            }
+            println!("watching {} files for changes...", files.len());
+        }
+        sleep(Duration::from_millis(500));
+    }
+}
+
+fn watched(aoc: &AoC, day: u8) -> Result<Vec<PathBuf>> {
+    let mut files = vec![PathBuf::from(format!("./src/bin/day{day}.rs"))];
+    files.push(aoc.input_path(day));
+    files.extend(aoc.example_paths(day)?);
+    Ok(files)
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn rerun(aoc: &AoC, day: u8, prev: &mut HashMap<PathBuf, Answers>) -> Result<()> {
+    let bin = format!("day{day}");
+    let status = Command::new("cargo")
+        .args(["build", "--quiet", "--bin", &bin])
+        .status()?;
+    if !status.success() {
+        bail!("build failed");
+    }
+    // examples first, they are cheap and catch most mistakes
+    let mut inputs = aoc.example_paths(day)?;
+    inputs.push(aoc.input_path(day));
+    for input in inputs {
+        println!("== {}", input.display());
+        let mut cmd = Command::new("cargo");
+        cmd.args(["run", "--quiet", "--bin", &bin]);
+        if input != aoc.input_path(day) {
+            cmd.env(INPUT_ENV, &input);
+        }
+        let output = cmd.output()?;
+        if !output.status.success() {
+            print!("{}", String::from_utf8_lossy(&output.stderr));
+        }
+        // a day that panics part way still printed the parts before, the rest show as missing
+        let answers = Answers::parse(&String::from_utf8_lossy(&output.stdout));
+        let known = Answers::load(&input)?;
+        for diff in answers.diff(prev.get(&input), known.as_ref()) {
+            println!("{diff}");
+        }
+        // keep comparing against the last complete run
+        if output.status.success() {
+            prev.insert(input, answers);
+        }
+    }
+    Ok(())
+}
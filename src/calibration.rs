@@ -0,0 +1,333 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::io::{self, BufRead};
+
+use anyhow::{bail, Context, Result};
+use rayon::prelude::*;
+
+pub const ENGLISH: [&str; 9] = [
+    "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+];
+
+/// The tokens a scanner recognises, each standing for a digit.
+#[derive(Debug, Clone, Default)]
+pub struct Vocabulary {
+    tokens: Vec<(String, u8)>,
+}
+
+impl Vocabulary {
+    /// Just `0` to `9`.
+    pub fn digits() -> Self {
+        Self::default().tokens((0..=9).map(|d| (d.to_string(), d)))
+    }
+
+    /// Digits plus the English words for one to nine.
+    pub fn english() -> Self {
+        Self::digits().numerals(ENGLISH)
+    }
+
+    /// Adds the words for one to nine in some language.
+    pub fn numerals(self, words: [&str; 9]) -> Self {
+        self.tokens(words.into_iter().zip(1..))
+    }
+
+    pub fn tokens<S: Into<String>>(mut self, tokens: impl IntoIterator<Item = (S, u8)>) -> Self {
+        self.tokens
+            .extend(tokens.into_iter().map(|(t, d)| (t.into(), d)));
+        self
+    }
+}
+
+/// A recognised token: `line[start..end]` reads as `digit`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Match {
+    pub start: usize,
+    pub end: usize,
+    pub digit: u8,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Calibration {
+    pub first: Match,
+    pub last: Match,
+}
+
+impl Calibration {
+    pub fn value(&self) -> u64 {
+        self.first.digit as u64 * 10 + self.last.digit as u64
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Node {
+    next: BTreeMap<u8, usize>,
+    fail: usize,
+    /// Tokens ending here, including those reached through `fail`, as `(length, digit)`.
+    out: Vec<(usize, u8)>,
+}
+
+/// An Aho–Corasick automaton over a [`Vocabulary`], finding every (possibly overlapping)
+/// token in a single pass.
+#[derive(Debug, Clone)]
+pub struct Scanner {
+    nodes: Vec<Node>,
+}
+
+impl Scanner {
+    pub fn new(vocabulary: &Vocabulary) -> Self {
+        let mut nodes = vec![Node::default()];
+        for (token, digit) in &vocabulary.tokens {
+            let mut cur = 0;
+            for &b in token.as_bytes() {
+                cur = match nodes[cur].next.get(&b) {
+                    Some(&n) => n,
+                    None => {
+                        nodes.push(Node::default());
+                        let n = nodes.len() - 1;
+                        nodes[cur].next.insert(b, n);
+                        n
+                    }
+                };
+            }
+            if !token.is_empty() {
+                nodes[cur].out.push((token.len(), *digit));
+            }
+        }
+        let mut queue = nodes[0].next.values().copied().collect::<VecDeque<_>>();
+        while let Some(n) = queue.pop_front() {
+            for (b, child) in nodes[n].next.clone() {
+                let mut f = nodes[n].fail;
+                let fail = loop {
+                    match nodes[f].next.get(&b) {
+                        Some(&t) if t != child => break t,
+                        _ if f == 0 => break 0,
+                        _ => f = nodes[f].fail,
+                    }
+                };
+                nodes[child].fail = fail;
+                let inherited = nodes[fail].out.clone();
+                nodes[child].out.extend(inherited);
+                queue.push_back(child);
+            }
+        }
+        Self { nodes }
+    }
+
+    /// Every token in `line`, ordered by where they end.
+    pub fn matches<'a>(&'a self, line: &'a str) -> impl Iterator<Item = Match> + 'a {
+        self.scan(line.as_bytes())
+    }
+
+    fn scan<'a>(&'a self, line: &'a [u8]) -> impl Iterator<Item = Match> + 'a {
+        let mut cur = 0;
+        line.iter().enumerate().flat_map(move |(i, &b)| {
+            cur = loop {
+                match self.nodes[cur].next.get(&b) {
+                    Some(&n) => break n,
+                    None if cur == 0 => break 0,
+                    None => cur = self.nodes[cur].fail,
+                }
+            };
+            self.nodes[cur].out.iter().map(move |&(len, digit)| Match {
+                start: i + 1 - len,
+                end: i + 1,
+                digit,
+            })
+        })
+    }
+
+    /// The token starting first and the one ending last.
+    pub fn calibrate(&self, line: &str) -> Result<Calibration> {
+        match self.first_last(line.as_bytes()) {
+            Some(c) => Ok(c),
+            None => bail!("no digit in {line:?}"),
+        }
+    }
+
+    fn first_last(&self, line: &[u8]) -> Option<Calibration> {
+        let mut matches = self.scan(line);
+        let m = matches.next()?;
+        let (first, last) = matches.fold((m, m), |(first, last), m| {
+            (
+                if m.start < first.start { m } else { first },
+                if m.end >= last.end { m } else { last },
+            )
+        });
+        Some(Calibration { first, last })
+    }
+
+    /// Calibrates `reader` one line at a time, calling `each` with every line's number and value
+    /// (`None` if it has no digit). Invalid UTF-8 is fine, only the tokens have to match. Fails
+    /// if the total overflows.
+    pub fn read(
+        &self,
+        mut reader: impl BufRead,
+        mut each: impl FnMut(usize, Option<u64>),
+    ) -> Result<Summary> {
+        let mut summary = Summary::default();
+        let mut buf = vec![];
+        while next_line(&mut reader, &mut buf)? {
+            summary.lines += 1;
+            let value = self.first_last(&buf).map(|c| c.value());
+            summary.add(summary.lines, value)?;
+            each(summary.lines, value);
+        }
+        Ok(summary)
+    }
+
+    /// Like [`Scanner::read`] without the per-line callback, reading `chunk` lines at a time and
+    /// calibrating each chunk in parallel.
+    pub fn par_read(&self, mut reader: impl BufRead, chunk: usize) -> Result<Summary> {
+        let mut summary = Summary::default();
+        let mut lines = vec![];
+        loop {
+            lines.clear();
+            let mut buf = vec![];
+            while lines.len() < chunk.max(1) && next_line(&mut reader, &mut buf)? {
+                lines.push(std::mem::take(&mut buf));
+            }
+            if lines.is_empty() {
+                summary.missing.sort_unstable();
+                return Ok(summary);
+            }
+            let offset = summary.lines;
+            let part = lines
+                .par_iter()
+                .enumerate()
+                .try_fold(Summary::default, |mut s, (i, line)| {
+                    s.lines += 1;
+                    s.add(offset + i + 1, self.first_last(line).map(|c| c.value()))?;
+                    Ok(s)
+                })
+                .try_reduce(Summary::default, Summary::merge)?;
+            summary = summary.merge(part)?;
+        }
+    }
+}
+
+/// Reads the next line into `buf` without its line ending, `false` at the end of input.
+fn next_line(reader: &mut impl BufRead, buf: &mut Vec<u8>) -> io::Result<bool> {
+    buf.clear();
+    if reader.read_until(b'\n', buf)? == 0 {
+        return Ok(false);
+    }
+    if buf.last() == Some(&b'\n') {
+        buf.pop();
+        if buf.last() == Some(&b'\r') {
+            buf.pop();
+        }
+    }
+    Ok(true)
+}
+
+const OVERFLOW: &str = "calibration total overflows u64";
+
+/// What calibrating a whole document came to.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Summary {
+    pub total: u64,
+    pub lines: usize,
+    /// Numbers (from 1) of the lines without any digit, in order.
+    pub missing: Vec<usize>,
+}
+
+impl Summary {
+    fn add(&mut self, line: usize, value: Option<u64>) -> Result<()> {
+        match value {
+            Some(v) => {
+                self.total = self.total.checked_add(v).context(OVERFLOW)?;
+            }
+            None => self.missing.push(line),
+        }
+        Ok(())
+    }
+
+    /// Leaves `missing` unsorted, the caller sorts it once at the end.
+    fn merge(mut self, other: Self) -> Result<Self> {
+        self.total = self.total.checked_add(other.total).context(OVERFLOW)?;
+        self.lines += other.lines;
+        self.missing.extend(other.missing);
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overlapping() {
+        let scanner = Scanner::new(&Vocabulary::english());
+        let found = scanner.matches("eightwothree").collect::<Vec<_>>();
+        assert_eq!(
+            found.iter().map(|m| (m.start, m.digit)).collect::<Vec<_>>(),
+            [(0, 8), (4, 2), (7, 3)]
+        );
+        let c = scanner.calibrate("xtwone3four").unwrap();
+        assert_eq!((c.first.start, c.first.end), (1, 4));
+        assert_eq!(c.value(), 24);
+        assert_eq!(scanner.calibrate("zoneight234").unwrap().value(), 14);
+        assert_eq!(scanner.calibrate("sevenine").unwrap().value(), 79);
+        assert_eq!(scanner.calibrate("treb7uchet").unwrap().value(), 77);
+        assert!(scanner.calibrate("nothing here").is_err());
+    }
+
+    #[test]
+    fn vocabularies() {
+        let digits = Scanner::new(&Vocabulary::digits());
+        assert_eq!(digits.calibrate("two1nine9").unwrap().value(), 19);
+
+        let german = Scanner::new(&Vocabulary::digits().numerals([
+            "eins", "zwei", "drei", "vier", "fünf", "sechs", "sieben", "acht", "neun",
+        ]));
+        assert_eq!(german.calibrate("xfünfzigxdreizehn").unwrap().value(), 53);
+
+        // one token being a suffix of another must still be reported
+        let custom =
+            Scanner::new(&Vocabulary::default().tokens([("abcd", 1), ("bc", 2), ("c", 3)]));
+        let found = custom.matches("abcd").map(|m| m.digit).collect::<Vec<_>>();
+        assert_eq!(found, [2, 3, 1]);
+    }
+
+    #[test]
+    fn streaming() {
+        let scanner = Scanner::new(&Vocabulary::english());
+        let doc = b"two1nine\r\nnothing\n\xffeight\xfe7\nsevenine";
+        let mut values = vec![];
+        let summary = scanner
+            .read(&doc[..], |line, v| values.push((line, v)))
+            .unwrap();
+        assert_eq!(
+            values,
+            [(1, Some(29)), (2, None), (3, Some(87)), (4, Some(79))]
+        );
+        let expected = Summary {
+            total: 29 + 87 + 79,
+            lines: 4,
+            missing: vec![2],
+        };
+        assert_eq!(summary, expected);
+        for chunk in [0, 1, 3, 100] {
+            assert_eq!(scanner.par_read(&doc[..], chunk).unwrap(), expected);
+        }
+
+        // lines without digits keep their numbers across chunk boundaries
+        let doc = (1..=10_000)
+            .map(|i| if i % 7 == 0 { "x" } else { "a9b" })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let summary = scanner.par_read(doc.as_bytes(), 64).unwrap();
+        assert_eq!(summary.missing, (7..=10_000).step_by(7).collect::<Vec<_>>());
+        assert_eq!(summary.total, 99 * (10_000 - 10_000 / 7));
+    }
+
+    #[test]
+    fn overflow() {
+        let mut summary = Summary {
+            total: u64::MAX - 99,
+            ..Summary::default()
+        };
+        summary.add(1, Some(99)).unwrap();
+        assert!(summary.add(2, Some(1)).is_err());
+        assert!(summary.clone().merge(summary).is_err());
+    }
+}
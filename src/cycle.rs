@@ -0,0 +1,193 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// The shape of the sequence `x0, f(x0), f(f(x0)), ...`: after `prefix` steps it repeats every
+/// `period` steps.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Cycle {
+    pub prefix: usize,
+    pub period: usize,
+}
+
+impl Cycle {
+    /// The smallest step whose state equals the state at step `n`.
+    pub fn reduce(&self, n: u64) -> usize {
+        let prefix = self.prefix as u64;
+        if n < prefix {
+            n as usize
+        } else {
+            (prefix + (n - prefix) % self.period as u64) as usize
+        }
+    }
+}
+
+/// Floyd's tortoise and hare, keeps two states around.
+pub fn floyd<S: Clone + PartialEq>(start: S, mut f: impl FnMut(&S) -> S) -> Cycle {
+    let mut tortoise = f(&start);
+    let mut hare = f(&tortoise);
+    while tortoise != hare {
+        tortoise = f(&tortoise);
+        let next = f(&hare);
+        hare = f(&next);
+    }
+    let mut prefix = 0;
+    tortoise = start;
+    while tortoise != hare {
+        tortoise = f(&tortoise);
+        hare = f(&hare);
+        prefix += 1;
+    }
+    let mut period = 1;
+    hare = f(&tortoise);
+    while tortoise != hare {
+        hare = f(&hare);
+        period += 1;
+    }
+    Cycle { prefix, period }
+}
+
+/// Brent's algorithm, same memory as [`floyd`] but fewer calls to `f`.
+pub fn brent<S: Clone + PartialEq>(start: S, mut f: impl FnMut(&S) -> S) -> Cycle {
+    let mut power = 1;
+    let mut period = 1;
+    let mut tortoise = start.clone();
+    let mut hare = f(&start);
+    while tortoise != hare {
+        if power == period {
+            tortoise = hare.clone();
+            power *= 2;
+            period = 0;
+        }
+        hare = f(&hare);
+        period += 1;
+    }
+    let mut prefix = 0;
+    tortoise = start.clone();
+    hare = start;
+    for _ in 0..period {
+        hare = f(&hare);
+    }
+    while tortoise != hare {
+        tortoise = f(&tortoise);
+        hare = f(&hare);
+        prefix += 1;
+    }
+    Cycle { prefix, period }
+}
+
+/// Remembers a fingerprint of every state rather than the state itself; a matching fingerprint
+/// is confirmed by replaying from `start`, so collisions cannot produce a wrong answer.
+pub fn fingerprinted<S, K>(
+    start: S,
+    mut f: impl FnMut(&S) -> S,
+    mut fingerprint: impl FnMut(&S) -> K,
+) -> Cycle
+where
+    S: Clone + PartialEq,
+    K: Hash + Eq,
+{
+    let mut seen: HashMap<K, Vec<usize>> = HashMap::new();
+    let mut cur = start.clone();
+    for i in 0.. {
+        let key = fingerprint(&cur);
+        if let Some(candidates) = seen.get(&key) {
+            for &j in candidates {
+                if advance(start.clone(), j as u64, &mut f) == cur {
+                    return Cycle {
+                        prefix: j,
+                        period: i - j,
+                    };
+                }
+            }
+        }
+        seen.entry(key).or_default().push(i);
+        cur = f(&cur);
+    }
+    unreachable!()
+}
+
+fn advance<S>(mut state: S, n: u64, f: &mut impl FnMut(&S) -> S) -> S {
+    for _ in 0..n {
+        state = f(&state);
+    }
+    state
+}
+
+/// The state after applying `f` to `start` exactly `n` times, skipping whole cycles. Looks for
+/// the cycle as in [`brent`] while stepping, so `f` never runs more than `n` times.
+pub fn nth<S: Clone + PartialEq>(start: S, n: u64, mut f: impl FnMut(&S) -> S) -> S {
+    let (mut power, mut period) = (1, 1);
+    let mut tortoise = start.clone();
+    let mut hare = start;
+    for step in 1..=n {
+        hare = f(&hare);
+        if hare == tortoise {
+            return advance(hare, (n - step) % period, &mut f);
+        }
+        if power == period {
+            tortoise = hare.clone();
+            power *= 2;
+            period = 0;
+        }
+        period += 1;
+    }
+    hare
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 0 -> 1 -> 2 -> 3 -> 4 -> 5 -> 6 -> 3 -> ...
+    fn f(&x: &u32) -> u32 {
+        if x == 6 {
+            3
+        } else {
+            x + 1
+        }
+    }
+
+    #[test]
+    fn detect() {
+        let expected = Cycle {
+            prefix: 3,
+            period: 4,
+        };
+        assert_eq!(floyd(0, f), expected);
+        assert_eq!(brent(0, f), expected);
+        assert_eq!(fingerprinted(0, f, |&x| x), expected);
+        // every state collides, still exact
+        assert_eq!(fingerprinted(0, f, |_| ()), expected);
+        assert_eq!(
+            brent(3, f),
+            Cycle {
+                prefix: 0,
+                period: 4
+            }
+        );
+    }
+
+    #[test]
+    fn skip_ahead() {
+        assert_eq!(nth(0, 2, f), 2);
+        assert_eq!(nth(0, 7, f), 3);
+        assert_eq!(nth(0, 1_000_000_000, f), 3 + (1_000_000_000 - 3) % 4);
+        assert_eq!(
+            nth(0, 10u64.pow(18), f),
+            3 + ((10u64.pow(18) - 3) % 4) as u32
+        );
+        assert_eq!(nth(0, 0, f), 0);
+        assert_eq!(nth(3, 5, f), 4);
+    }
+
+    #[test]
+    fn no_cycle() {
+        let mut calls = 0;
+        let x = nth(0u64, 1000, |x| {
+            calls += 1;
+            x + 1
+        });
+        assert_eq!((x, calls), (1000, 1000));
+        assert_eq!(nth(0u64, 3, |x| x + 1), 3);
+    }
+}
@@ -0,0 +1,280 @@
+use std::collections::VecDeque;
+
+use anyhow::{bail, Context, Result};
+use itertools::Itertools;
+use num::integer::gcd;
+
+use crate::grid::Direction;
+
+/// A `(row, col)` lattice point.
+pub type Point = (i64, i64);
+
+/// A closed polygon on the integer lattice; the last vertex connects back to the first.
+#[derive(Debug, Clone)]
+pub struct Polygon {
+    vertices: Vec<Point>,
+}
+
+impl Polygon {
+    pub fn from_vertices(vertices: Vec<Point>) -> Result<Self> {
+        let mut vertices = vertices;
+        if vertices.len() > 1 && vertices.first() == vertices.last() {
+            vertices.pop();
+        }
+        let vertices = drop_straight(vertices);
+        if vertices.len() < 3 {
+            bail!(
+                "a polygon needs at least 3 vertices, got {}",
+                vertices.len()
+            );
+        }
+        let polygon = Self { vertices };
+        polygon.check_simple()?;
+        Ok(polygon)
+    }
+
+    /// Walks `steps` from `start`, the walk must end where it began.
+    pub fn from_steps(
+        start: Point,
+        steps: impl IntoIterator<Item = (Direction, i64)>,
+    ) -> Result<Self> {
+        let mut vertices = vec![start];
+        let mut cur = start;
+        for (d, n) in steps {
+            let (dr, dc) = d.offset();
+            cur = (
+                (dr as i64)
+                    .checked_mul(n)
+                    .and_then(|dr| cur.0.checked_add(dr))
+                    .context("overflow")?,
+                (dc as i64)
+                    .checked_mul(n)
+                    .and_then(|dc| cur.1.checked_add(dc))
+                    .context("overflow")?,
+            );
+            vertices.push(cur);
+        }
+        if cur != start {
+            bail!("polygon does not close: ends at {cur:?}, started at {start:?}");
+        }
+        Self::from_vertices(vertices)
+    }
+
+    pub fn vertices(&self) -> &[Point] {
+        &self.vertices
+    }
+
+    fn edges(&self) -> impl Iterator<Item = (Point, Point)> + '_ {
+        self.vertices.iter().copied().circular_tuple_windows()
+    }
+
+    /// Twice the signed area (shoelace), positive when the vertices go counter-clockwise
+    /// in `(row, col)` space. Doubled so lattice polygons stay integral.
+    pub fn double_signed_area(&self) -> Result<i128> {
+        self.edges().try_fold(0i128, |acc, ((r1, c1), (r2, c2))| {
+            let term = (r1 as i128 * c2 as i128).checked_sub(r2 as i128 * c1 as i128);
+            term.and_then(|t| acc.checked_add(t)).context("overflow")
+        })
+    }
+
+    /// Lattice points lying on the boundary, vertices included.
+    pub fn boundary_points(&self) -> Result<i128> {
+        self.edges().try_fold(0i128, |acc, ((r1, c1), (r2, c2))| {
+            let dr = (r2 as i128 - r1 as i128).abs();
+            let dc = (c2 as i128 - c1 as i128).abs();
+            acc.checked_add(gcd(dr, dc)).context("overflow")
+        })
+    }
+
+    /// Lattice points strictly inside, by Pick's theorem: `A = i + b / 2 - 1`.
+    pub fn interior_points(&self) -> Result<i128> {
+        let a2 = self.double_signed_area()?.abs();
+        let b = self.boundary_points()?;
+        Ok((a2 - b + 2) / 2)
+    }
+
+    /// Interior plus boundary points, i.e. how many unit cells a trench along the boundary digs out.
+    pub fn dug_volume(&self) -> Result<i128> {
+        self.interior_points()?
+            .checked_add(self.boundary_points()?)
+            .context("overflow")
+    }
+
+    /// Sweeps the edges by row, only testing pairs whose row spans overlap.
+    fn check_simple(&self) -> Result<()> {
+        let edges = self.edges().collect_vec();
+        let n = edges.len();
+        let rows = |(a, b): (Point, Point)| (a.0.min(b.0), a.0.max(b.0));
+        let order = (0..n).sorted_by_key(|&i| rows(edges[i]).0).collect_vec();
+        let mut active: Vec<usize> = vec![];
+        for i in order {
+            let (a, b) = edges[i];
+            if a == b {
+                bail!("degenerate edge at {a:?}");
+            }
+            let top = rows(edges[i]).0;
+            active.retain(|&j| rows(edges[j]).1 >= top);
+            for &j in &active {
+                let (c, d) = edges[j];
+                let (lo, hi) = (i.min(j), i.max(j));
+                let crosses = if hi == lo + 1 || (lo == 0 && hi == n - 1) {
+                    // sharing an endpoint is fine, folding back onto each other is not
+                    let ((p, shared), (_, q)) = if hi == lo + 1 {
+                        (edges[lo], edges[hi])
+                    } else {
+                        (edges[hi], edges[lo])
+                    };
+                    orient(p, shared, q) == 0 && same_side(shared, p, q)
+                } else {
+                    intersects((a, b), (c, d))
+                };
+                if crosses {
+                    bail!("polygon self-intersects: {a:?}-{b:?} and {c:?}-{d:?}");
+                }
+            }
+            active.push(i);
+        }
+        Ok(())
+    }
+}
+
+/// Drops vertices in the middle of a straight run, they add nothing but work.
+fn drop_straight(vertices: Vec<Point>) -> Vec<Point> {
+    let straight = |a: Point, v: Point, b: Point| orient(a, v, b) == 0 && !same_side(v, a, b);
+    let mut out: VecDeque<Point> = VecDeque::with_capacity(vertices.len());
+    for v in vertices {
+        while out.len() >= 2 && straight(out[out.len() - 2], out[out.len() - 1], v) {
+            out.pop_back();
+        }
+        out.push_back(v);
+    }
+    // the run may continue across the first vertex
+    loop {
+        let n = out.len();
+        if n <= 3 {
+            break;
+        }
+        if straight(out[n - 2], out[n - 1], out[0]) {
+            out.pop_back();
+        } else if straight(out[n - 1], out[0], out[1]) {
+            out.pop_front();
+        } else {
+            break;
+        }
+    }
+    out.into()
+}
+
+fn orient(a: Point, b: Point, c: Point) -> i128 {
+    let (a, b, c) = (
+        (a.0 as i128, a.1 as i128),
+        (b.0 as i128, b.1 as i128),
+        (c.0 as i128, c.1 as i128),
+    );
+    ((b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0)).signum()
+}
+
+/// For collinear points, whether `p` and `q` lie on the same side of `shared`.
+fn same_side(shared: Point, p: Point, q: Point) -> bool {
+    let dot = (p.0 as i128 - shared.0 as i128) * (q.0 as i128 - shared.0 as i128)
+        + (p.1 as i128 - shared.1 as i128) * (q.1 as i128 - shared.1 as i128);
+    dot > 0
+}
+
+fn within(a: Point, b: Point, p: Point) -> bool {
+    p.0 >= a.0.min(b.0) && p.0 <= a.0.max(b.0) && p.1 >= a.1.min(b.1) && p.1 <= a.1.max(b.1)
+}
+
+fn intersects((a, b): (Point, Point), (c, d): (Point, Point)) -> bool {
+    let disjoint = a.0.max(b.0) < c.0.min(d.0)
+        || c.0.max(d.0) < a.0.min(b.0)
+        || a.1.max(b.1) < c.1.min(d.1)
+        || c.1.max(d.1) < a.1.min(b.1);
+    if disjoint {
+        return false;
+    }
+    let (o1, o2, o3, o4) = (
+        orient(a, b, c),
+        orient(a, b, d),
+        orient(c, d, a),
+        orient(c, d, b),
+    );
+    if o1 * o2 < 0 && o3 * o4 < 0 {
+        return true;
+    }
+    (o1 == 0 && within(a, b, c))
+        || (o2 == 0 && within(a, b, d))
+        || (o3 == 0 && within(c, d, a))
+        || (o4 == 0 && within(c, d, b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Direction::{Down, Left, Right, Up};
+
+    #[test]
+    fn square() {
+        let p = Polygon::from_steps((0, 0), [(Right, 2), (Down, 2), (Left, 2), (Up, 2)]).unwrap();
+        assert_eq!(p.double_signed_area().unwrap().abs(), 8);
+        assert_eq!(p.boundary_points().unwrap(), 8);
+        assert_eq!(p.interior_points().unwrap(), 1);
+        assert_eq!(p.dug_volume().unwrap(), 9);
+
+        let reversed =
+            Polygon::from_vertices(p.vertices().iter().rev().copied().collect()).unwrap();
+        assert_eq!(
+            reversed.double_signed_area().unwrap(),
+            -p.double_signed_area().unwrap()
+        );
+    }
+
+    #[test]
+    fn triangle() {
+        let p = Polygon::from_vertices(vec![(0, 0), (0, 4), (3, 0)]).unwrap();
+        assert_eq!(p.double_signed_area().unwrap().abs(), 12);
+        assert_eq!(p.boundary_points().unwrap(), 8);
+        assert_eq!(p.interior_points().unwrap(), 3);
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(Polygon::from_steps((0, 0), [(Right, 2), (Down, 2), (Left, 1)]).is_err());
+        assert!(Polygon::from_vertices(vec![(0, 0), (0, 1)]).is_err());
+        // bow tie
+        assert!(Polygon::from_vertices(vec![(0, 0), (2, 2), (0, 2), (2, 0)]).is_err());
+        // folds back onto itself
+        assert!(Polygon::from_steps(
+            (0, 0),
+            [(Right, 2), (Left, 1), (Down, 1), (Up, 1), (Left, 1)]
+        )
+        .is_err());
+        assert!(Polygon::from_steps((0, 0), [(Right, i64::MAX), (Left, i64::MAX)]).is_err());
+        // two far apart edges of a long staircase touch
+        let mut steps = vec![];
+        for _ in 0..500 {
+            steps.extend([(Right, 1), (Down, 1)]);
+        }
+        steps.extend([(Left, 500), (Up, 499), (Right, 600), (Up, 1), (Left, 600)]);
+        assert!(Polygon::from_steps((0, 0), steps).is_err());
+    }
+
+    #[test]
+    fn straight_runs() {
+        // split edges, including one running through the first vertex
+        let p =
+            Polygon::from_vertices(vec![(0, 1), (0, 2), (0, 3), (2, 3), (2, 2), (2, 0), (0, 0)])
+                .unwrap();
+        assert_eq!(p.vertices(), [(0, 3), (2, 3), (2, 0), (0, 0)]);
+        assert_eq!(p.double_signed_area().unwrap().abs(), 12);
+
+        let mut steps = vec![];
+        for _ in 0..1000 {
+            steps.extend([(Right, 1), (Down, 1)]);
+        }
+        steps.extend([(Left, 1000), (Up, 1000)]);
+        let p = Polygon::from_steps((0, 0), steps).unwrap();
+        assert_eq!(p.vertices().len(), 2002);
+        assert_eq!(p.dug_volume().unwrap(), (1..=1001).sum::<i128>() + 1000);
+    }
+}
@@ -0,0 +1,238 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Debug, Display, Write};
+
+use anyhow::{bail, Context, Result};
+use itertools::Itertools;
+use petgraph::graphmap::{DiGraphMap, GraphMap, NodeTrait, UnGraphMap};
+use petgraph::unionfind::UnionFind;
+use petgraph::EdgeType;
+use rustworkx_core::connectivity::stoer_wagner_min_cut;
+
+/// Nodes and edges to draw in color when exporting DOT.
+#[derive(Debug, Clone)]
+pub struct Highlight<N> {
+    nodes: HashSet<N>,
+    edges: HashSet<(N, N)>,
+}
+
+impl<N: NodeTrait> Default for Highlight<N> {
+    fn default() -> Self {
+        Self {
+            nodes: HashSet::new(),
+            edges: HashSet::new(),
+        }
+    }
+}
+
+impl<N: NodeTrait> Highlight<N> {
+    pub fn nodes(mut self, nodes: impl IntoIterator<Item = N>) -> Self {
+        self.nodes.extend(nodes);
+        self
+    }
+
+    pub fn edges(mut self, edges: impl IntoIterator<Item = (N, N)>) -> Self {
+        for (a, b) in edges {
+            self.nodes.extend([a, b]);
+            self.edges.insert((a, b));
+        }
+        self
+    }
+
+    pub fn path(self, path: &[N]) -> Self {
+        self.edges(path.iter().copied().tuple_windows())
+    }
+
+    pub fn cycle(self, cycle: &[N]) -> Self {
+        self.edges(cycle.iter().copied().circular_tuple_windows())
+    }
+
+    fn has_edge(&self, a: N, b: N, directed: bool) -> bool {
+        self.edges.contains(&(a, b)) || (!directed && self.edges.contains(&(b, a)))
+    }
+}
+
+/// Graphviz source for `g`, labelling edges with `edge_label` where it returns something.
+pub fn to_dot<N, E, Ty>(
+    g: &GraphMap<N, E, Ty>,
+    highlight: &Highlight<N>,
+    edge_label: impl Fn(&E) -> Option<String>,
+) -> String
+where
+    N: NodeTrait + Display,
+    Ty: EdgeType,
+{
+    const HIGHLIGHT: &str = "color=red, penwidth=2";
+    let directed = g.is_directed();
+    let (kind, arrow) = if directed {
+        ("digraph", "->")
+    } else {
+        ("graph", "--")
+    };
+    let mut dot = format!("{kind} {{\n");
+    for n in g.nodes() {
+        let attrs = if highlight.nodes.contains(&n) {
+            format!(" [{HIGHLIGHT}]")
+        } else {
+            String::new()
+        };
+        writeln!(dot, "    \"{n}\"{attrs};").unwrap();
+    }
+    for (a, b, e) in g.all_edges() {
+        let attrs = edge_label(e)
+            .map(|l| format!("label=\"{l}\""))
+            .into_iter()
+            .chain(
+                highlight
+                    .has_edge(a, b, directed)
+                    .then(|| HIGHLIGHT.to_owned()),
+            )
+            .join(", ");
+        let attrs = if attrs.is_empty() {
+            attrs
+        } else {
+            format!(" [{attrs}]")
+        };
+        writeln!(dot, "    \"{a}\" {arrow} \"{b}\"{attrs};").unwrap();
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+/// Connected components, ignoring edge direction. Nodes keep their insertion order.
+pub fn components<N: NodeTrait, E, Ty: EdgeType>(g: &GraphMap<N, E, Ty>) -> Vec<Vec<N>> {
+    let index = g
+        .nodes()
+        .enumerate()
+        .map(|(i, n)| (n, i))
+        .collect::<HashMap<_, _>>();
+    let mut sets = UnionFind::new(g.node_count());
+    for (a, b, _) in g.all_edges() {
+        sets.union(index[&a], index[&b]);
+    }
+    let labels = sets.into_labeling();
+    g.nodes()
+        .enumerate()
+        .into_group_map_by(|&(i, _)| labels[i])
+        .into_values()
+        .sorted_by_key(|c| c[0].0)
+        .map(|c| c.into_iter().map(|(_, n)| n).collect_vec())
+        .collect()
+}
+
+/// Strongly connected components, in reverse topological order.
+pub fn sccs<N: NodeTrait, E>(g: &DiGraphMap<N, E>) -> Vec<Vec<N>> {
+    petgraph::algo::tarjan_scc(g)
+}
+
+/// A set of edges whose removal splits a graph in two.
+#[derive(Debug, Clone)]
+pub struct Cut<N> {
+    pub edges: Vec<(N, N)>,
+    pub sides: [Vec<N>; 2],
+}
+
+/// Checks that removing `edges` from `g` leaves exactly two components, with every removed edge
+/// running between them.
+pub fn verify_cut<N: NodeTrait + Debug, E: Clone>(
+    g: &UnGraphMap<N, E>,
+    edges: &[(N, N)],
+) -> Result<Cut<N>> {
+    let mut rest = g.clone();
+    for &(a, b) in edges {
+        rest.remove_edge(a, b)
+            .with_context(|| format!("no edge {a:?} -- {b:?} to cut"))?;
+    }
+    let Ok([left, right]) = <[_; 2]>::try_from(components(&rest)) else {
+        bail!("cutting {edges:?} does not split the graph in two");
+    };
+    for (a, b) in edges {
+        if left.contains(a) == left.contains(b) {
+            bail!("cut edge {a:?} -- {b:?} does not cross between the sides");
+        }
+    }
+    Ok(Cut {
+        edges: edges.to_vec(),
+        sides: [left, right],
+    })
+}
+
+/// The minimum edge cut, by Stoer–Wagner with unit weights, verified with [`verify_cut`].
+pub fn min_cut<N: NodeTrait + Debug, E: Clone>(g: &UnGraphMap<N, E>) -> Result<Cut<N>> {
+    let (_, partition) =
+        stoer_wagner_min_cut(g, |_| anyhow::Ok(1))?.context("a cut needs at least two nodes")?;
+    let side = partition.into_iter().collect::<HashSet<_>>();
+    let edges = g
+        .all_edges()
+        .filter(|(a, b, _)| side.contains(a) != side.contains(b))
+        .map(|(a, b, _)| (a, b))
+        .collect_vec();
+    verify_cut(g, &edges)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // two triangles joined by a single bridge
+    fn bridged() -> UnGraphMap<u32, ()> {
+        UnGraphMap::from_edges([(1, 2), (2, 3), (3, 1), (3, 4), (4, 5), (5, 6), (6, 4)])
+    }
+
+    #[test]
+    fn cuts() {
+        let g = bridged();
+        let cut = min_cut(&g).unwrap();
+        assert_eq!(cut.edges.len(), 1);
+        let mut sides = cut.sides.map(|mut s| {
+            s.sort();
+            s
+        });
+        sides.sort();
+        assert_eq!(sides, [vec![1, 2, 3], vec![4, 5, 6]]);
+
+        assert!(verify_cut(&g, &[(1, 2)]).is_err());
+        assert!(verify_cut(&g, &[(1, 4)]).is_err());
+        // splits off 4, but the second edge stays inside one side
+        assert!(verify_cut(&g, &[(3, 4), (4, 5), (5, 6)]).is_err());
+    }
+
+    #[test]
+    fn components_and_sccs() {
+        let mut g = bridged();
+        g.remove_edge(3, 4);
+        g.add_node(7);
+        assert_eq!(components(&g), [vec![1, 2, 3], vec![4, 5, 6], vec![7]]);
+
+        let d = DiGraphMap::<u32, ()>::from_edges([(1, 2), (2, 1), (2, 3), (3, 4), (4, 3)]);
+        let sccs = sccs(&d)
+            .into_iter()
+            .map(|mut c| {
+                c.sort();
+                c
+            })
+            .collect_vec();
+        assert_eq!(sccs, [vec![3, 4], vec![1, 2]]);
+        assert_eq!(components(&d).len(), 1);
+    }
+
+    #[test]
+    fn dot() {
+        let g = DiGraphMap::<&str, char>::from_edges([("a", "b", 'L'), ("b", "a", 'R')]);
+        let dot = to_dot(&g, &Highlight::default().path(&["a", "b"]), |&e| {
+            Some(e.to_string())
+        });
+        assert_eq!(
+            dot,
+            "digraph {
+    \"a\" [color=red, penwidth=2];
+    \"b\" [color=red, penwidth=2];
+    \"a\" -> \"b\" [label=\"L\", color=red, penwidth=2];
+    \"b\" -> \"a\" [label=\"R\"];
+}
+"
+        );
+        let u = UnGraphMap::<&str, ()>::from_edges([("a", "b")]);
+        let dot = to_dot(&u, &Highlight::default().edges([("b", "a")]), |_| None);
+        assert!(dot.contains("\"a\" -- \"b\" [color=red, penwidth=2];"));
+    }
+}
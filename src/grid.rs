@@ -0,0 +1,226 @@
+use itertools::Itertools;
+
+pub use ::grid::Grid;
+
+/// A `(row, col)` position, the same indexing `grid::Grid` uses.
+pub type Pos = (usize, usize);
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum Direction {
+    Up = 0,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+    ];
+
+    pub fn rotate_right(self) -> Self {
+        match self {
+            Self::Up => Self::Right,
+            Self::Down => Self::Left,
+            Self::Left => Self::Up,
+            Self::Right => Self::Down,
+        }
+    }
+
+    pub fn rotate_left(self) -> Self {
+        match self {
+            Self::Up => Self::Left,
+            Self::Down => Self::Right,
+            Self::Left => Self::Down,
+            Self::Right => Self::Up,
+        }
+    }
+
+    pub fn opposite(self) -> Self {
+        match self {
+            Self::Up => Self::Down,
+            Self::Down => Self::Up,
+            Self::Left => Self::Right,
+            Self::Right => Self::Left,
+        }
+    }
+
+    /// `(d_row, d_col)`
+    pub fn offset(self) -> (isize, isize) {
+        match self {
+            Self::Up => (-1, 0),
+            Self::Down => (1, 0),
+            Self::Left => (0, -1),
+            Self::Right => (0, 1),
+        }
+    }
+
+    /// Moves one step, only checking for underflow; see [`GridExt::nav`] for a bounded move.
+    pub fn nav(self, pos: Pos) -> Option<Pos> {
+        offset(pos, self.offset())
+    }
+}
+
+/// Like [`Direction`] but including diagonals, clockwise starting from `Up`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum Direction8 {
+    Up = 0,
+    UpRight,
+    Right,
+    DownRight,
+    Down,
+    DownLeft,
+    Left,
+    UpLeft,
+}
+
+impl Direction8 {
+    pub const ALL: [Direction8; 8] = [
+        Direction8::Up,
+        Direction8::UpRight,
+        Direction8::Right,
+        Direction8::DownRight,
+        Direction8::Down,
+        Direction8::DownLeft,
+        Direction8::Left,
+        Direction8::UpLeft,
+    ];
+
+    /// `(d_row, d_col)`
+    pub fn offset(self) -> (isize, isize) {
+        match self {
+            Self::Up => (-1, 0),
+            Self::UpRight => (-1, 1),
+            Self::Right => (0, 1),
+            Self::DownRight => (1, 1),
+            Self::Down => (1, 0),
+            Self::DownLeft => (1, -1),
+            Self::Left => (0, -1),
+            Self::UpLeft => (-1, -1),
+        }
+    }
+
+    pub fn nav(self, pos: Pos) -> Option<Pos> {
+        offset(pos, self.offset())
+    }
+}
+
+impl From<Direction> for Direction8 {
+    fn from(d: Direction) -> Self {
+        match d {
+            Direction::Up => Self::Up,
+            Direction::Down => Self::Down,
+            Direction::Left => Self::Left,
+            Direction::Right => Self::Right,
+        }
+    }
+}
+
+fn offset(pos: Pos, (dr, dc): (isize, isize)) -> Option<Pos> {
+    let (r, c) = pos;
+    r.checked_add_signed(dr).zip(c.checked_add_signed(dc))
+}
+
+pub fn manhattan(a: Pos, b: Pos) -> usize {
+    a.0.abs_diff(b.0) + a.1.abs_diff(b.1)
+}
+
+/// Bounds-aware navigation over a `grid::Grid`.
+pub trait GridExt<T> {
+    fn contains(&self, pos: Pos) -> bool;
+
+    fn nav(&self, pos: Pos, dir: Direction) -> Option<Pos> {
+        dir.nav(pos).filter(|&p| self.contains(p))
+    }
+
+    fn nav8(&self, pos: Pos, dir: Direction8) -> Option<Pos> {
+        dir.nav(pos).filter(|&p| self.contains(p))
+    }
+
+    fn neighbors(&self, pos: Pos) -> impl Iterator<Item = (Direction, Pos)> + '_ {
+        Direction::ALL
+            .into_iter()
+            .filter_map(move |d| self.nav(pos, d).map(|p| (d, p)))
+    }
+
+    fn neighbors8(&self, pos: Pos) -> impl Iterator<Item = (Direction8, Pos)> + '_ {
+        Direction8::ALL
+            .into_iter()
+            .filter_map(move |d| self.nav8(pos, d).map(|p| (d, p)))
+    }
+
+    fn position(&self, pred: impl FnMut(&T) -> bool) -> Option<Pos>;
+}
+
+impl<T> GridExt<T> for Grid<T> {
+    fn contains(&self, (r, c): Pos) -> bool {
+        r < self.rows() && c < self.cols()
+    }
+
+    fn position(&self, mut pred: impl FnMut(&T) -> bool) -> Option<Pos> {
+        self.indexed_iter()
+            .find_map(|(pos, t)| pred(t).then_some(pos))
+    }
+}
+
+pub fn parse(input: &str) -> Grid<char> {
+    parse_with(input, |c| c)
+}
+
+/// Parses one row per non-empty line, mapping each char into a cell.
+pub fn parse_with<T>(input: &str, mut f: impl FnMut(char) -> T) -> Grid<T> {
+    let mut cols = 0;
+    let mut cells = vec![];
+    for line in input.lines().map(str::trim_end).filter(|l| !l.is_empty()) {
+        let len = cells.len();
+        cells.extend(line.chars().map(&mut f));
+        let row_len = cells.len() - len;
+        if cols == 0 {
+            cols = row_len;
+        }
+        assert_eq!(row_len, cols, "uneven row: {line}");
+    }
+    Grid::from_vec(cells, cols)
+}
+
+pub fn render<T>(grid: &Grid<T>, mut f: impl FnMut(&T) -> char) -> String {
+    grid.iter_rows()
+        .map(|row| row.map(&mut f).collect::<String>())
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_render() {
+        let grid = parse("#.#\n..#\n");
+        assert_eq!(grid.size(), (2, 3));
+        assert_eq!(grid[(1, 2)], '#');
+        assert_eq!(render(&grid, |&c| c), "#.#\n..#");
+
+        let bits = parse_with("#.\n.#", |c| c == '#');
+        assert_eq!(render(&bits, |&b| if b { '1' } else { '0' }), "10\n01");
+    }
+
+    #[test]
+    fn navigation() {
+        let grid = parse("...\n...");
+        assert_eq!(grid.nav((0, 0), Direction::Up), None);
+        assert_eq!(grid.nav((0, 0), Direction::Right), Some((0, 1)));
+        assert_eq!(grid.nav((1, 2), Direction::Down), None);
+        assert_eq!(grid.neighbors((0, 0)).count(), 2);
+        assert_eq!(grid.neighbors8((0, 1)).count(), 5);
+        assert_eq!(grid.neighbors8((1, 1)).count(), 5);
+
+        for d in Direction::ALL {
+            assert_eq!(d.rotate_left().rotate_right(), d);
+            assert_eq!(d.rotate_right().rotate_right(), d.opposite());
+        }
+        assert_eq!(manhattan((1, 5), (4, 2)), 6);
+    }
+}
@@ -0,0 +1,434 @@
+use std::ops::Range;
+
+use itertools::Itertools;
+use num::PrimInt;
+
+/// A set of integers stored as sorted, disjoint, non-touching half-open ranges.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+pub struct IntervalSet<T> {
+    ranges: Vec<Range<T>>,
+}
+
+impl<T: PrimInt> IntervalSet<T> {
+    pub fn new() -> Self {
+        Self { ranges: vec![] }
+    }
+
+    pub fn ranges(&self) -> &[Range<T>] {
+        &self.ranges
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// How many values the set holds.
+    pub fn measure(&self) -> T {
+        self.ranges
+            .iter()
+            .fold(T::zero(), |acc, r| acc + (r.end - r.start))
+    }
+
+    pub fn min(&self) -> Option<T> {
+        self.ranges.first().map(|r| r.start)
+    }
+
+    pub fn contains(&self, x: T) -> bool {
+        let i = self.ranges.partition_point(|r| r.end <= x);
+        self.ranges.get(i).is_some_and(|r| r.start <= x)
+    }
+
+    pub fn insert(&mut self, range: Range<T>) {
+        if range.is_empty() {
+            return;
+        }
+        // everything touching `range` collapses into a single range
+        let lo = self.ranges.partition_point(|r| r.end < range.start);
+        let hi = self.ranges.partition_point(|r| r.start <= range.end);
+        let merged = if lo < hi {
+            self.ranges[lo].start.min(range.start)..self.ranges[hi - 1].end.max(range.end)
+        } else {
+            range
+        };
+        self.ranges.splice(lo..hi, [merged]);
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut res = self.clone();
+        for r in &other.ranges {
+            res.insert(r.clone());
+        }
+        res
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut res = vec![];
+        let (mut i, mut j) = (0, 0);
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let (a, b) = (&self.ranges[i], &other.ranges[j]);
+            let (start, end) = (a.start.max(b.start), a.end.min(b.end));
+            if start < end {
+                res.push(start..end);
+            }
+            if a.end < b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        Self { ranges: res }
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut res = vec![];
+        let mut j = 0;
+        for r in &self.ranges {
+            let mut start = r.start;
+            while j < other.ranges.len() && other.ranges[j].end <= start {
+                j += 1;
+            }
+            let mut k = j;
+            while k < other.ranges.len() && other.ranges[k].start < r.end {
+                let o = &other.ranges[k];
+                if o.start > start {
+                    res.push(start..o.start);
+                }
+                start = start.max(o.end);
+                k += 1;
+            }
+            if start < r.end {
+                res.push(start..r.end);
+            }
+        }
+        Self { ranges: res }
+    }
+
+    /// Cuts the ranges at every point in `points`, so that no returned range straddles one.
+    pub fn split_at(&self, points: impl IntoIterator<Item = T>) -> Vec<Range<T>> {
+        let points = points.into_iter().sorted().dedup().collect_vec();
+        let mut res = vec![];
+        for r in &self.ranges {
+            let mut start = r.start;
+            let from = points.partition_point(|&p| p <= r.start);
+            for &p in points[from..].iter().take_while(|&&p| p < r.end) {
+                res.push(start..p);
+                start = p;
+            }
+            res.push(start..r.end);
+        }
+        res
+    }
+}
+
+impl<T: PrimInt> From<Range<T>> for IntervalSet<T> {
+    fn from(range: Range<T>) -> Self {
+        Self::from_iter([range])
+    }
+}
+
+impl<T: PrimInt> FromIterator<Range<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = Range<T>>>(iter: I) -> Self {
+        let mut set = Self::new();
+        for r in iter {
+            set.insert(r);
+        }
+        set
+    }
+}
+
+/// A linear piece of a [`PiecewiseMap`]: `src` is translated onto `dst..dst + src.len()`.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Segment<T> {
+    pub src: Range<T>,
+    pub dst: T,
+}
+
+impl<T: PrimInt> Segment<T> {
+    fn apply(&self, x: T) -> T {
+        // shift by the offset rather than the distance from `src.start`, which may be huge
+        if self.dst >= self.src.start {
+            x + (self.dst - self.src.start)
+        } else {
+            x - (self.src.start - self.dst)
+        }
+    }
+
+    fn dst_range(&self) -> Range<T> {
+        self.dst..self.apply(self.src.end)
+    }
+
+    fn inverse(&self) -> Self {
+        Self {
+            src: self.dst_range(),
+            dst: self.src.start,
+        }
+    }
+}
+
+/// Translation rules over disjoint ranges; values outside every rule map to themselves.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+pub struct PiecewiseMap<T> {
+    segments: Vec<Segment<T>>,
+}
+
+impl<T: PrimInt> PiecewiseMap<T> {
+    pub fn identity() -> Self {
+        Self { segments: vec![] }
+    }
+
+    /// Builds a map from `(src, dst)` rules, `None` if any two source ranges overlap.
+    pub fn new(rules: impl IntoIterator<Item = Segment<T>>) -> Option<Self> {
+        let segments = rules
+            .into_iter()
+            .filter(|s| !s.src.is_empty())
+            .sorted_by_key(|s| s.src.start)
+            .collect_vec();
+        if segments
+            .iter()
+            .tuple_windows()
+            .any(|(a, b)| a.src.end > b.src.start)
+        {
+            return None;
+        }
+        Some(Self::normalized(segments))
+    }
+
+    // drops identity pieces and joins pieces that continue each other
+    fn normalized(segments: Vec<Segment<T>>) -> Self {
+        let mut res: Vec<Segment<T>> = vec![];
+        for s in segments.into_iter().filter(|s| s.src.start != s.dst) {
+            match res.last_mut() {
+                Some(last) if last.src.end == s.src.start && last.apply(s.src.start) == s.dst => {
+                    last.src.end = s.src.end
+                }
+                _ => res.push(s),
+            }
+        }
+        Self { segments: res }
+    }
+
+    pub fn segments(&self) -> &[Segment<T>] {
+        &self.segments
+    }
+
+    /// Every point where the map changes from one linear piece to another.
+    pub fn breakpoints(&self) -> Vec<T> {
+        self.segments
+            .iter()
+            .flat_map(|s| [s.src.start, s.src.end])
+            .dedup()
+            .collect()
+    }
+
+    fn segment(&self, x: T) -> Option<&Segment<T>> {
+        let i = self.segments.partition_point(|s| s.src.end <= x);
+        self.segments.get(i).filter(|s| s.src.start <= x)
+    }
+
+    pub fn apply(&self, x: T) -> T {
+        self.segment(x).map_or(x, |s| s.apply(x))
+    }
+
+    /// Every linear piece of the map over `range`, identity pieces included.
+    fn pieces(&self, range: Range<T>) -> Vec<Segment<T>> {
+        let set = IntervalSet::from(range);
+        set.split_at(self.breakpoints())
+            .into_iter()
+            .map(|r| Segment {
+                dst: self.apply(r.start),
+                src: r,
+            })
+            .collect()
+    }
+
+    pub fn apply_set(&self, set: &IntervalSet<T>) -> IntervalSet<T> {
+        set.ranges
+            .iter()
+            .flat_map(|r| self.pieces(r.clone()))
+            .map(|s| s.dst_range())
+            .collect()
+    }
+
+    /// The map applying `self` first and then `next`.
+    pub fn then(&self, next: &Self) -> Self {
+        let mut segments = vec![];
+        let mut add = |src: Range<T>| {
+            for piece in self.pieces(src) {
+                let back = piece.inverse();
+                for inner in next.pieces(piece.dst_range()) {
+                    segments.push(Segment {
+                        src: back.apply(inner.src.start)..back.apply(inner.src.end),
+                        dst: inner.dst,
+                    });
+                }
+            }
+        };
+        // wherever `self` is the identity, `next` still applies
+        let domain = IntervalSet::from(T::min_value()..T::max_value());
+        for r in domain.split_at(self.breakpoints().into_iter().chain(next.breakpoints())) {
+            add(r);
+        }
+        Self::normalized(segments)
+    }
+
+    /// The inverse map, or `None` when the map is not a bijection.
+    pub fn invert(&self) -> Option<Self> {
+        let src: IntervalSet<T> = self.segments.iter().map(|s| s.src.clone()).collect();
+        let dst: IntervalSet<T> = self.segments.iter().map(|s| s.dst_range()).collect();
+        let dst_len = self
+            .segments
+            .iter()
+            .fold(T::zero(), |acc, s| acc + (s.src.end - s.src.start));
+        // overlapping destinations would shrink the set, and unless the rules permute the very
+        // values they cover, the identity part would collide with them
+        if dst.measure() != dst_len || src != dst {
+            return None;
+        }
+        Self::new(self.segments.iter().map(Segment::inverse))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::XorShift;
+
+    fn set(ranges: &[Range<i64>]) -> IntervalSet<i64> {
+        ranges.iter().cloned().collect()
+    }
+
+    #[test]
+    fn set_ops() {
+        let a = set(&[0..5, 10..15]);
+        assert_eq!(set(&[0..5, 5..7]), IntervalSet::from(0..7));
+        assert_eq!(a.union(&IntervalSet::from(4..11)), IntervalSet::from(0..15));
+        let b = IntervalSet::from(3..12);
+        assert_eq!(a.intersection(&b).ranges(), [3..5, 10..12]);
+        assert_eq!(a.difference(&b).ranges(), [0..3, 12..15]);
+        assert_eq!(a.split_at([2, 10, 12, 20]), [0..2, 2..5, 10..12, 12..15]);
+        assert_eq!(a.measure(), 10);
+        assert!(a.contains(14) && !a.contains(5));
+    }
+
+    #[test]
+    fn map_ops() {
+        // seed-to-soil from the day5 example
+        let m = PiecewiseMap::new([
+            Segment {
+                src: 98..100,
+                dst: 50,
+            },
+            Segment {
+                src: 50..98,
+                dst: 52,
+            },
+        ])
+        .unwrap();
+        assert_eq!(
+            [0, 49, 50, 97, 98, 99, 100].map(|x| m.apply(x)),
+            [0, 49, 52, 99, 50, 51, 100]
+        );
+        assert_eq!(
+            m.apply_set(&set(&[79..93, 55..68])).ranges(),
+            [57..70, 81..95]
+        );
+        assert_eq!(m.breakpoints(), [50, 98, 100]);
+
+        let inv = m.invert().unwrap();
+        let id = m.then(&inv);
+        assert!(id.segments().is_empty());
+        assert_eq!(inv.apply(99), 97);
+
+        let overlapping = [
+            Segment { src: 0..10, dst: 5 },
+            Segment { src: 5..10, dst: 0 },
+        ];
+        assert!(PiecewiseMap::new(overlapping).is_none());
+        let lossy = PiecewiseMap::new([Segment {
+            src: 0..10,
+            dst: 20,
+        }])
+        .unwrap();
+        assert!(lossy.invert().is_none());
+    }
+
+    struct Rng(XorShift);
+
+    impl Rng {
+        fn next(&mut self, n: i64) -> i64 {
+            self.0.below(n as u64) as i64
+        }
+
+        fn set(&mut self) -> IntervalSet<i64> {
+            (0..self.next(5))
+                .map(|_| {
+                    let s = self.next(60);
+                    s..s + self.next(15)
+                })
+                .collect()
+        }
+
+        fn map(&mut self) -> PiecewiseMap<i64> {
+            let mut start = 0;
+            let mut segments = vec![];
+            for _ in 0..self.next(5) {
+                start += self.next(10);
+                let len = self.next(10);
+                segments.push(Segment {
+                    src: start..start + len,
+                    dst: self.next(80),
+                });
+                start += len;
+            }
+            PiecewiseMap::new(segments).unwrap()
+        }
+    }
+
+    fn members(set: &IntervalSet<i64>) -> Vec<i64> {
+        (-10..100).filter(|&x| set.contains(x)).collect()
+    }
+
+    #[test]
+    fn properties() {
+        let mut rng = Rng(XorShift::new(0x2023_1205));
+        for _ in 0..500 {
+            let (a, b) = (rng.set(), rng.set());
+            let (ma, mb) = (members(&a), members(&b));
+            let expect = |f: fn(bool, bool) -> bool| {
+                (-10..100)
+                    .filter(|x| f(ma.contains(x), mb.contains(x)))
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(members(&a.union(&b)), expect(|x, y| x || y));
+            assert_eq!(members(&a.intersection(&b)), expect(|x, y| x && y));
+            assert_eq!(members(&a.difference(&b)), expect(|x, y| x && !y));
+            assert!(a
+                .ranges()
+                .iter()
+                .tuple_windows()
+                .all(|(x, y)| x.end < y.start));
+
+            let (f, g) = (rng.map(), rng.map());
+            let fg = f.then(&g);
+            for x in -10..100 {
+                assert_eq!(fg.apply(x), g.apply(f.apply(x)));
+            }
+            let mut image = ma.iter().map(|&x| f.apply(x)).collect::<Vec<_>>();
+            image.sort();
+            image.dedup();
+            assert_eq!(
+                f.apply_set(&a)
+                    .ranges()
+                    .iter()
+                    .flat_map(|r| r.clone())
+                    .collect::<Vec<_>>(),
+                image
+            );
+            if let Some(inv) = f.invert() {
+                for x in -10..100 {
+                    assert_eq!(inv.apply(f.apply(x)), x);
+                }
+            }
+        }
+    }
+}
@@ -1,5 +1,6 @@
-use std::fs::{create_dir_all, read_to_string, File};
-use std::io::Write;
+use std::env;
+use std::fs::{create_dir_all, read_dir, read_to_string, File};
+use std::io::{BufReader, Write};
 use std::path::PathBuf;
 
 use anyhow::{bail, Context, Result};
@@ -8,8 +9,27 @@ use reqwest::header::{COOKIE, USER_AGENT};
 use time::Month::December;
 use time::{Date, OffsetDateTime, PrimitiveDateTime, UtcOffset};
 
+pub mod answers;
+pub mod calibration;
+pub mod cycle;
+pub mod geometry;
+pub mod graph;
+pub mod grid;
+pub mod interval;
+pub mod linalg;
+pub mod numtheory;
+pub mod parse;
+pub mod plane;
+pub mod poly;
+pub mod rng;
+pub mod search;
+pub mod viz;
+
 const YEAR: i32 = 2023;
 
+/// Overrides the input file read by [`AoC::read_input`], used to run a day against an example.
+pub const INPUT_ENV: &str = "AOC_INPUT";
+
 pub fn aoc() -> Result<AoC> {
     let token = include_str!("../.token").trim().to_owned();
     AoC::new(YEAR, token)
@@ -35,17 +55,53 @@ impl AoC {
         })
     }
 
+    pub fn input_path(&self, day: u8) -> PathBuf {
+        self.inputs.join(format!("{day}.txt"))
+    }
+
+    /// Example inputs are stored next to the real input as `{day}.example*.txt`.
+    pub fn example_paths(&self, day: u8) -> Result<Vec<PathBuf>> {
+        let prefix = format!("{day}.example");
+        let mut paths = vec![];
+        for entry in read_dir(&self.inputs)? {
+            let path = entry?.path();
+            let is_example = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with(&prefix) && n.ends_with(".txt"));
+            if is_example {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+        Ok(paths)
+    }
+
     pub fn read_input(&self, day: u8) -> Result<String> {
-        let path = self.inputs.join(format!("{day}.txt"));
-        let input = if !path.exists() {
+        let path = self.input_file(day)?;
+        read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))
+    }
+
+    /// Like [`AoC::read_input`], for reading the input a bit at a time.
+    pub fn open_input(&self, day: u8) -> Result<BufReader<File>> {
+        let path = self.input_file(day)?;
+        let file =
+            File::open(&path).with_context(|| format!("failed to open {}", path.display()))?;
+        Ok(BufReader::new(file))
+    }
+
+    /// Where the input is, downloading it first if need be.
+    fn input_file(&self, day: u8) -> Result<PathBuf> {
+        if let Some(path) = env::var_os(INPUT_ENV) {
+            return Ok(path.into());
+        }
+        let path = self.input_path(day);
+        if !path.exists() {
             let input = self.fetch_input(day)?;
-            let mut file = File::create(path)?;
+            let mut file = File::create(&path)?;
             file.write_all(input.as_bytes())?;
-            input
-        } else {
-            read_to_string(path)?
-        };
-        Ok(input)
+        }
+        Ok(path)
     }
 
     fn fetch_input(&self, day: u8) -> Result<String> {
@@ -0,0 +1,126 @@
+use anyhow::{bail, ensure, Result};
+use num::Num;
+
+/// Solves `a * x = b` by Gaussian elimination, exactly as long as `T` is exact (e.g.
+/// `Ratio<i128>`, or `BigRational` when intermediate values may not fit). Fails unless the
+/// system has a unique solution.
+pub fn solve<T: Num + Clone>(a: &[Vec<T>], b: &[T]) -> Result<Vec<T>> {
+    let n = a.len();
+    ensure!(b.len() == n, "{n} rows but {} right hand sides", b.len());
+    ensure!(
+        a.iter().all(|row| row.len() == n),
+        "the matrix must be {n}x{n}"
+    );
+    let mut m = a
+        .iter()
+        .zip(b)
+        .map(|(row, b)| {
+            let mut row = row.clone();
+            row.push(b.clone());
+            row
+        })
+        .collect::<Vec<_>>();
+    for col in 0..n {
+        let Some(pivot) = (col..n).find(|&r| !m[r][col].is_zero()) else {
+            bail!("singular matrix: no pivot in column {col}");
+        };
+        m.swap(col, pivot);
+        let pivot = m[col].clone();
+        for r in (0..n).filter(|&r| r != col) {
+            eliminate(&mut m[r], &pivot, col);
+        }
+    }
+    Ok(m.into_iter()
+        .enumerate()
+        .map(|(i, row)| row[n].clone() / row[i].clone())
+        .collect())
+}
+
+/// The determinant, by elimination; zero exactly when [`solve`] would fail on a square matrix.
+pub fn determinant<T: Num + Clone>(a: &[Vec<T>]) -> T {
+    let n = a.len();
+    let mut m = a.to_vec();
+    let mut det = T::one();
+    for col in 0..n {
+        let Some(pivot) = (col..n).find(|&r| !m[r][col].is_zero()) else {
+            return T::zero();
+        };
+        if pivot != col {
+            m.swap(col, pivot);
+            det = T::zero() - det;
+        }
+        let pivot = m[col].clone();
+        for row in &mut m[col + 1..] {
+            eliminate(row, &pivot, col);
+        }
+        det = det * m[col][col].clone();
+    }
+    det
+}
+
+/// Subtracts the multiple of `pivot` that zeroes `row[col]`.
+fn eliminate<T: Num + Clone>(row: &mut [T], pivot: &[T], col: usize) {
+    if row[col].is_zero() {
+        return;
+    }
+    let factor = row[col].clone() / pivot[col].clone();
+    for (x, p) in row[col..].iter_mut().zip(&pivot[col..]) {
+        *x = x.clone() - factor.clone() * p.clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::rational::Ratio;
+    use num::{BigInt, BigRational};
+
+    use super::*;
+
+    fn ratios(rows: &[&[i128]]) -> Vec<Vec<Ratio<i128>>> {
+        rows.iter()
+            .map(|row| row.iter().map(|&x| Ratio::from(x)).collect())
+            .collect()
+    }
+
+    #[test]
+    fn small() {
+        let a = ratios(&[&[0, 2, 1], &[1, 1, 1], &[2, 1, 0]]);
+        let b = [5, 4, 4].map(Ratio::from);
+        assert_eq!(solve(&a, &b).unwrap(), [1, 2, 1].map(Ratio::from));
+        assert_eq!(determinant(&a), Ratio::from(3));
+
+        let a = ratios(&[&[2, 0], &[0, 3]]);
+        let x = solve(&a, &[Ratio::from(1), Ratio::from(1)]).unwrap();
+        assert_eq!(x, [Ratio::new(1, 2), Ratio::new(1, 3)]);
+    }
+
+    #[test]
+    fn singular() {
+        let a = ratios(&[&[1, 2], &[2, 4]]);
+        assert!(solve(&a, &[Ratio::from(1), Ratio::from(2)]).is_err());
+        assert_eq!(determinant(&a), Ratio::from(0));
+        assert!(solve(&ratios(&[&[1, 2]]), &[Ratio::from(1)]).is_err());
+    }
+
+    #[test]
+    fn big() {
+        // entries whose products overflow i128
+        let big = BigInt::from(10).pow(30);
+        let a = vec![
+            vec![
+                BigRational::from(big.clone()),
+                BigRational::from_integer(1.into()),
+            ],
+            vec![
+                BigRational::from_integer(1.into()),
+                BigRational::from(big.clone()),
+            ],
+        ];
+        let x = [BigInt::from(3), BigInt::from(-7)];
+        let b = [
+            BigRational::from(&big * &x[0] + &x[1]),
+            BigRational::from(&x[0] + &big * &x[1]),
+        ];
+        assert_eq!(solve(&a, &b).unwrap(), x.map(BigRational::from));
+    }
+}
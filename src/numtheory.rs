@@ -0,0 +1,210 @@
+use itertools::Itertools;
+use num::integer::{gcd, lcm};
+
+use crate::cycle;
+
+/// Returns `(g, x, y)` with `a * x + b * y == g == gcd(a, b)`.
+pub fn ext_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        (a.abs(), a.signum(), 0)
+    } else {
+        let (g, x, y) = ext_gcd(b, a.rem_euclid(b));
+        (g, y, x - a.div_euclid(b) * y)
+    }
+}
+
+/// `x` in `0..m` with `a * x ≡ 1 (mod m)`, if `a` and `m` are coprime.
+pub fn mod_inverse(a: i128, m: i128) -> Option<i128> {
+    let (g, x, _) = ext_gcd(a.rem_euclid(m), m);
+    (g == 1).then(|| x.rem_euclid(m))
+}
+
+/// The times `t ≡ rem (mod modulus)`, with `rem` in `0..modulus`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Congruence {
+    pub rem: i128,
+    pub modulus: i128,
+}
+
+impl Congruence {
+    pub fn new(rem: i128, modulus: i128) -> Self {
+        assert!(modulus > 0, "modulus must be positive, got {modulus}");
+        Self {
+            rem: rem.rem_euclid(modulus),
+            modulus,
+        }
+    }
+
+    /// Chinese remainder theorem for moduli that need not be coprime; `None` if the two never
+    /// agree.
+    pub fn combine(&self, other: &Self) -> Option<Self> {
+        let g = gcd(self.modulus, other.modulus);
+        let diff = other.rem - self.rem;
+        if diff % g != 0 {
+            return None;
+        }
+        let m = other.modulus / g;
+        let k = (diff / g).rem_euclid(m) * mod_inverse(self.modulus / g, m)? % m;
+        Some(Self::new(
+            self.rem + self.modulus * k,
+            lcm(self.modulus, other.modulus),
+        ))
+    }
+
+    /// The smallest matching time that is at least `min`.
+    pub fn first_from(&self, min: i128) -> i128 {
+        min + (self.rem - min).rem_euclid(self.modulus)
+    }
+}
+
+/// Combines all congruences into one, `None` if they have no common solution.
+pub fn crt(congruences: impl IntoIterator<Item = Congruence>) -> Option<Congruence> {
+    congruences
+        .into_iter()
+        .try_fold(Congruence::new(0, 1), |acc, c| acc.combine(&c))
+}
+
+/// The times at which an eventually periodic process hits its target: each time in `once`,
+/// then from `start` on, every `t` with `t ≡ r (mod period)` for some `r` in `periodic`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Hits {
+    pub once: Vec<u64>,
+    pub start: u64,
+    pub period: u64,
+    pub periodic: Vec<u64>,
+}
+
+impl Hits {
+    /// Hits at `first`, then every `period` after it.
+    pub fn every(first: u64, period: u64) -> Self {
+        Self {
+            once: vec![],
+            start: first,
+            period,
+            periodic: vec![first],
+        }
+    }
+
+    /// Runs `f` from `start` until it cycles, recording every step whose state `is_hit`.
+    pub fn find<S: Clone + PartialEq>(
+        start: S,
+        mut f: impl FnMut(&S) -> S,
+        mut is_hit: impl FnMut(&S) -> bool,
+    ) -> Self {
+        let cycle = cycle::brent(start.clone(), &mut f);
+        let (prefix, period) = (cycle.prefix as u64, cycle.period as u64);
+        let mut hits = Self {
+            once: vec![],
+            start: prefix,
+            period,
+            periodic: vec![],
+        };
+        let mut cur = start;
+        for t in 0..prefix + period {
+            if is_hit(&cur) {
+                if t < prefix {
+                    hits.once.push(t);
+                } else {
+                    hits.periodic.push(t);
+                }
+            }
+            cur = f(&cur);
+        }
+        hits
+    }
+
+    pub fn contains(&self, t: u64) -> bool {
+        self.once.contains(&t)
+            || (t >= self.start
+                && self
+                    .periodic
+                    .iter()
+                    .any(|&r| t >= r && (t - r).is_multiple_of(self.period)))
+    }
+}
+
+/// The first time at which every process hits together.
+///
+/// Tries each combination of periodic hits, so the cost grows with the product of how often
+/// each process hits per period.
+pub fn first_common(hits: &[Hits]) -> Option<u64> {
+    let once = hits
+        .iter()
+        .flat_map(|h| h.once.iter().copied())
+        .filter(|&t| hits.iter().all(|h| h.contains(t)))
+        .min();
+    let min = hits.iter().map(|h| h.start).max().unwrap_or(0) as i128;
+    let periodic = hits
+        .iter()
+        .map(|h| {
+            h.periodic
+                .iter()
+                .map(|&r| Congruence::new(r as i128, h.period as i128))
+        })
+        .multi_cartesian_product()
+        .filter_map(crt)
+        .map(|c| c.first_from(min))
+        .min()
+        .and_then(|t| u64::try_from(t).ok());
+    once.into_iter().chain(periodic).min()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inverse() {
+        assert_eq!(ext_gcd(240, 46), (2, -9, 47));
+        assert_eq!(mod_inverse(3, 11), Some(4));
+        assert_eq!(mod_inverse(-3, 11), Some(7));
+        assert_eq!(mod_inverse(4, 6), None);
+    }
+
+    #[test]
+    fn chinese_remainder() {
+        let c = crt([
+            Congruence::new(2, 3),
+            Congruence::new(3, 5),
+            Congruence::new(2, 7),
+        ]);
+        assert_eq!(c, Some(Congruence::new(23, 105)));
+        // not coprime, but consistent
+        let c = crt([Congruence::new(2, 6), Congruence::new(5, 9)]);
+        assert_eq!(c, Some(Congruence::new(14, 18)));
+        assert_eq!(crt([Congruence::new(1, 4), Congruence::new(2, 6)]), None);
+        assert_eq!(Congruence::new(14, 18).first_from(20), 32);
+    }
+
+    #[test]
+    fn common_hits() {
+        // zero offset, plain lcm
+        assert_eq!(
+            first_common(&[Hits::every(4, 4), Hits::every(6, 6)]),
+            Some(12)
+        );
+        // offsets, lcm would say 15
+        assert_eq!(
+            first_common(&[Hits::every(2, 3), Hits::every(4, 5)]),
+            Some(14)
+        );
+        assert_eq!(first_common(&[Hits::every(1, 2), Hits::every(2, 2)]), None);
+
+        // 0 -> 1 -> .. -> 5 -> 2, hitting at 1 before the cycle and at 3 and 5 within it
+        let f = |&x: &u32| if x == 5 { 2 } else { x + 1 };
+        let a = Hits::find(0, f, |&x| x % 2 == 1);
+        assert_eq!(
+            a,
+            Hits {
+                once: vec![1],
+                start: 2,
+                period: 4,
+                periodic: vec![3, 5],
+            }
+        );
+        assert!(a.contains(9) && a.contains(11) && !a.contains(10));
+        assert_eq!(first_common(&[a.clone(), Hits::every(1, 10)]), Some(1));
+        assert_eq!(first_common(&[a.clone(), Hits::every(2, 10)]), None);
+        assert_eq!(first_common(&[a, Hits::every(4, 7)]), Some(11));
+    }
+}
@@ -0,0 +1,298 @@
+use std::any::type_name;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ParseError {
+    /// `expected` was not found in `input`.
+    Missing { expected: String, input: String },
+    /// `input` could not be parsed as a `ty`.
+    Invalid {
+        ty: &'static str,
+        input: String,
+        reason: String,
+    },
+    /// `input` had `found` fields where `expected` were wanted.
+    FieldCount {
+        expected: usize,
+        found: usize,
+        input: String,
+    },
+    /// `input` is shorter than the requested slice.
+    OutOfBounds { range: Range<usize>, input: String },
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Missing { expected, input } => write!(f, "expected {expected:?} in {input:?}"),
+            Self::Invalid { ty, input, reason } => {
+                write!(f, "invalid {ty} {input:?}: {reason}")
+            }
+            Self::FieldCount {
+                expected,
+                found,
+                input,
+            } => write!(f, "expected {expected} fields, found {found} in {input:?}"),
+            Self::OutOfBounds { range, input } => write!(f, "{range:?} out of bounds in {input:?}"),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+pub type Result<T> = std::result::Result<T, ParseError>;
+
+fn missing(expected: &str, input: &str) -> ParseError {
+    ParseError::Missing {
+        expected: expected.to_owned(),
+        input: input.to_owned(),
+    }
+}
+
+pub fn parse<T>(s: &str) -> Result<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    s.trim().parse().map_err(|e: T::Err| ParseError::Invalid {
+        ty: type_name::<T>(),
+        input: s.to_owned(),
+        reason: e.to_string(),
+    })
+}
+
+/// `str::split_once`, with both halves trimmed so spacing around `sep` doesn't matter.
+pub fn split_once<'a>(s: &'a str, sep: &str) -> Result<(&'a str, &'a str)> {
+    let split = match sep.trim() {
+        "" => s.trim().split_once(char::is_whitespace),
+        sep => s.split_once(sep),
+    };
+    split
+        .map(|(l, r)| (l.trim(), r.trim()))
+        .ok_or_else(|| missing(sep, s))
+}
+
+pub fn strip_prefix<'a>(s: &'a str, prefix: &str) -> Result<&'a str> {
+    match_loose(s.trim_start(), prefix)
+        .map(|end| s.trim_start()[end..].trim())
+        .ok_or_else(|| missing(prefix, s))
+}
+
+/// Matches `lit` at the start of `s`, where any run of whitespace in either may be any other,
+/// returning the end of the match.
+fn match_loose(s: &str, lit: &str) -> Option<usize> {
+    let mut chars = s.char_indices().peekable();
+    for c in lit.chars().filter(|c| !c.is_whitespace()) {
+        while chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+        chars.next_if(|&(_, x)| x == c)?;
+    }
+    Some(chars.peek().map_or(s.len(), |&(i, _)| i))
+}
+
+fn find_loose<'a>(s: &'a str, lit: &str) -> Option<(&'a str, &'a str)> {
+    s.char_indices()
+        .find_map(|(i, _)| match_loose(&s[i..], lit).map(|end| (&s[..i], &s[i + end..])))
+}
+
+/// Bounds-checked `&s[range]`.
+pub fn slice(s: &str, range: Range<usize>) -> Result<&str> {
+    s.get(range.clone()).ok_or_else(|| ParseError::OutOfBounds {
+        range,
+        input: s.to_owned(),
+    })
+}
+
+/// Whitespace separated values, e.g. `"79 14  55 13"`.
+pub fn numbers<T>(s: &str) -> Result<Vec<T>>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    s.split_ascii_whitespace().map(parse).collect()
+}
+
+/// Every signed integer appearing anywhere in `s`, ignoring whatever surrounds them. Fails on
+/// a number that doesn't fit in `T`, or a negative one when `T` is unsigned.
+pub fn ints<T>(s: &str) -> Result<Vec<T>>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let mut res = vec![];
+    let bytes = s.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        let neg = bytes[i] == b'-' && bytes.get(i + 1).is_some_and(u8::is_ascii_digit);
+        if bytes[i].is_ascii_digit() || neg {
+            let start = i;
+            i += 1;
+            while i < bytes.len() && bytes[i].is_ascii_digit() {
+                i += 1;
+            }
+            res.push(parse(&s[start..i])?);
+        } else {
+            i += 1;
+        }
+    }
+    Ok(res)
+}
+
+/// Blank-line separated blocks, with surrounding blank lines ignored.
+pub fn blocks(input: &str) -> impl Iterator<Item = String> + '_ {
+    let mut lines = input.lines().map(str::trim_end).peekable();
+    std::iter::from_fn(move || {
+        while lines.next_if(|l| l.is_empty()).is_some() {}
+        lines.peek()?;
+        let block = lines.by_ref().take_while(|l| !l.is_empty());
+        Some(block.collect::<Vec<_>>().join("\n"))
+    })
+}
+
+/// A tuple whose fields can each be parsed from a string.
+pub trait FromFields: Sized {
+    const LEN: usize;
+
+    fn from_fields(fields: &[&str]) -> Result<Self>;
+}
+
+macro_rules! impl_from_fields {
+    ($($t:ident),+) => {
+        impl<$($t),+> FromFields for ($($t,)+)
+        where
+            $($t: FromStr, $t::Err: Display),+
+        {
+            const LEN: usize = [$(stringify!($t)),+].len();
+
+            fn from_fields(fields: &[&str]) -> Result<Self> {
+                if fields.len() != Self::LEN {
+                    return Err(ParseError::FieldCount {
+                        expected: Self::LEN,
+                        found: fields.len(),
+                        input: fields.join(" "),
+                    });
+                }
+                let mut fields = fields.iter();
+                Ok(($(parse::<$t>(fields.next().unwrap())?,)+))
+            }
+        }
+    };
+}
+
+impl_from_fields!(A);
+impl_from_fields!(A, B);
+impl_from_fields!(A, B, C);
+impl_from_fields!(A, B, C, D);
+impl_from_fields!(A, B, C, D, E);
+impl_from_fields!(A, B, C, D, E, F);
+impl_from_fields!(A, B, C, D, E, F, G);
+
+/// Parses the whitespace separated fields of `s` into a tuple.
+pub fn fields<T: FromFields>(s: &str) -> Result<T> {
+    T::from_fields(&s.split_ascii_whitespace().collect::<Vec<_>>())
+}
+
+/// Parses `s` against a `template` where every `{}` is a field, e.g. `"{} = ({}, {})"`.
+///
+/// Literal parts are matched with surrounding whitespace ignored; a literal made only of
+/// whitespace separates fields by whitespace, and the last field takes the rest of the line.
+pub fn scan<T: FromFields>(s: &str, template: &str) -> Result<T> {
+    let literals = template.split("{}").map(str::trim).collect::<Vec<_>>();
+    let mut rest = strip_prefix(s, literals[0])?;
+    let mut fields = vec![];
+    for (i, &lit) in literals[1..].iter().enumerate() {
+        let last = i + 2 == literals.len();
+        let (field, next) = match lit {
+            "" if last => (rest, ""),
+            "" => rest
+                .split_once(char::is_whitespace)
+                .ok_or_else(|| missing(" ", rest))?,
+            _ => find_loose(rest, lit).ok_or_else(|| missing(lit, rest))?,
+        };
+        fields.push(field.trim());
+        rest = next.trim_start();
+    }
+    if !rest.trim().is_empty() {
+        return Err(ParseError::FieldCount {
+            expected: literals.len() - 1,
+            found: fields.len() + 1,
+            input: s.to_owned(),
+        });
+    }
+    T::from_fields(&fields)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn helpers() {
+        assert_eq!(
+            split_once("Game 1 :  3 blue", ": "),
+            Ok(("Game 1", "3 blue"))
+        );
+        assert_eq!(split_once(" 3   blue", " "), Ok(("3", "blue")));
+        assert_eq!(strip_prefix("seeds: 79 14", "seeds:"), Ok("79 14"));
+        assert_eq!(numbers::<u64>(" 79  14 55 "), Ok(vec![79, 14, 55]));
+        assert!(matches!(
+            numbers::<u64>("79 x"),
+            Err(ParseError::Invalid { ty: "u64", .. })
+        ));
+        assert_eq!(
+            ints::<i64>("19, 13, 30 @ -2,  1, -2"),
+            Ok(vec![19, 13, 30, -2, 1, -2])
+        );
+        assert_eq!(ints::<i32>("x=-3..-1, y=a-5"), Ok(vec![-3, -1, -5]));
+        assert_eq!(
+            ints::<i64>("x=9223372036854775807, y=-9223372036854775808"),
+            Ok(vec![i64::MAX, i64::MIN])
+        );
+        assert!(matches!(
+            ints::<i64>("x=9223372036854775808"),
+            Err(ParseError::Invalid { ty: "i64", .. })
+        ));
+        assert!(matches!(
+            ints::<u8>("1, 256"),
+            Err(ParseError::Invalid { ty: "u8", .. })
+        ));
+        assert!(ints::<u32>("3 @ -2").is_err());
+        assert_eq!(slice("AAA = (BBB, CCC)", 7..10), Ok("BBB"));
+        assert!(slice("AAA", 7..10).is_err());
+        assert_eq!(
+            blocks("\na\nb\n\n\nc\r\n\n").collect::<Vec<_>>(),
+            ["a\nb", "c"]
+        );
+    }
+
+    #[test]
+    fn tuples() {
+        assert_eq!(
+            fields::<(char, i64, String)>("R 6 (#70c710)"),
+            Ok(('R', 6, "(#70c710)".to_owned()))
+        );
+        assert!(matches!(
+            fields::<(char, i64)>("R 6 (#70c710)"),
+            Err(ParseError::FieldCount {
+                expected: 2,
+                found: 3,
+                ..
+            })
+        ));
+        let expected = Ok(("AAA".to_owned(), "BBB".to_owned(), "CCC".to_owned()));
+        assert_eq!(scan("AAA = (BBB, CCC)", "{} = ({}, {})"), expected);
+        assert_eq!(scan("AAA=( BBB,CCC )", "{} = ({}, {})"), expected);
+        assert_eq!(
+            scan::<(u32, String)>("Game 12: 3 blue", "Game {}: {}"),
+            Ok((12, "3 blue".to_owned()))
+        );
+        assert!(scan::<(u32, u32)>("Card  3:  5  6", "Card {}: {} {}").is_err());
+        assert_eq!(
+            scan::<(u32, u32, u32)>("Card  3:  5  6", "Card {}: {} {}"),
+            Ok((3, 5, 6))
+        );
+        assert!(scan::<(u32,)>("Card 3", "Game {}").is_err());
+    }
+}
@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+use std::ops::{Deref, Index};
+
+use crate::grid::{Direction, Grid, Pos};
+use crate::search;
+
+/// A signed `(row, col)` position on a [`Plane`].
+pub type IPos = (isize, isize);
+
+#[derive(Debug, Clone)]
+pub enum Edge<T> {
+    /// The grid repeats infinitely in every direction.
+    Repeat,
+    /// Everything outside the grid reads as this value.
+    Default(T),
+}
+
+/// An infinite view over a `Grid`, either tiling it or padding it with a default value.
+#[derive(Debug, Clone)]
+pub struct Plane<T> {
+    grid: Grid<T>,
+    edge: Edge<T>,
+}
+
+impl<T> Plane<T> {
+    pub fn repeat(grid: Grid<T>) -> Self {
+        Self {
+            grid,
+            edge: Edge::Repeat,
+        }
+    }
+
+    pub fn bounded(grid: Grid<T>, default: T) -> Self {
+        Self {
+            grid,
+            edge: Edge::Default(default),
+        }
+    }
+
+    /// Which copy of the grid `pos` falls into, `(0, 0)` being the original.
+    pub fn tile(&self, (row, col): IPos) -> IPos {
+        (
+            row.div_euclid(self.grid.rows() as isize),
+            col.div_euclid(self.grid.cols() as isize),
+        )
+    }
+
+    /// The position within the original grid that `pos` maps onto.
+    pub fn wrap(&self, (row, col): IPos) -> Pos {
+        (
+            row.rem_euclid(self.grid.rows() as isize) as usize,
+            col.rem_euclid(self.grid.cols() as isize) as usize,
+        )
+    }
+
+    pub fn get(&self, pos: IPos) -> &T {
+        match &self.edge {
+            Edge::Repeat => &self.grid[self.wrap(pos)],
+            Edge::Default(default) => self.grid.get(pos.0, pos.1).unwrap_or(default),
+        }
+    }
+
+    pub fn neighbors(&self, (row, col): IPos) -> impl Iterator<Item = (Direction, IPos)> {
+        Direction::ALL.into_iter().map(move |d| {
+            let (dr, dc) = d.offset();
+            (d, (row + dr, col + dc))
+        })
+    }
+
+    /// Breadth-first distances from `start` over cells accepted by `passable`, up to `max_steps`.
+    pub fn distances(
+        &self,
+        start: IPos,
+        max_steps: usize,
+        passable: impl Fn(&T) -> bool,
+    ) -> HashMap<IPos, usize> {
+        search::bfs_within([start], max_steps, |&pos| {
+            self.neighbors(pos)
+                .map(|(_, next)| next)
+                .filter(|&next| passable(self.get(next)))
+                .collect::<Vec<_>>()
+        })
+        .into_map()
+    }
+
+    /// Counts the positions accepted by `pred`, grouped by the tile they fall into.
+    pub fn count_by_tile<'a>(
+        &self,
+        positions: impl IntoIterator<Item = &'a IPos>,
+        mut pred: impl FnMut(&IPos) -> bool,
+    ) -> HashMap<IPos, usize> {
+        let mut counts = HashMap::new();
+        for pos in positions.into_iter().filter(|p| pred(p)) {
+            *counts.entry(self.tile(*pos)).or_default() += 1;
+        }
+        counts
+    }
+}
+
+impl<T> Index<IPos> for Plane<T> {
+    type Output = T;
+
+    fn index(&self, pos: IPos) -> &Self::Output {
+        self.get(pos)
+    }
+}
+
+impl<T> Deref for Plane<T> {
+    type Target = Grid<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.grid
+    }
+}
+
+pub fn signed((row, col): Pos) -> IPos {
+    (row as isize, col as isize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid;
+
+    #[test]
+    fn tiling() {
+        let plane = Plane::repeat(grid::parse("ab\ncd"));
+        assert_eq!(plane[(0, 0)], 'a');
+        assert_eq!(plane[(-1, -1)], 'd');
+        assert_eq!(plane[(4, 3)], 'b');
+        assert_eq!(plane.tile((-1, 2)), (-1, 1));
+        assert_eq!(plane.tile((1, 1)), (0, 0));
+
+        let plane = Plane::bounded(grid::parse("ab\ncd"), '#');
+        assert_eq!(plane[(1, 0)], 'c');
+        assert_eq!(plane[(-1, 0)], '#');
+        assert_eq!(plane[(2, 0)], '#');
+    }
+
+    #[test]
+    fn distances() {
+        let plane = Plane::repeat(grid::parse("..\n.#"));
+        let dist = plane.distances((0, 0), 2, |&c| c != '#');
+        assert_eq!(dist.len(), 9);
+        assert_eq!(dist.get(&(-1, -1)), None);
+        assert_eq!(dist[&(0, -2)], 2);
+
+        let counts = plane.count_by_tile(dist.keys(), |_| true);
+        assert_eq!(counts[&(0, 0)], 3);
+        assert_eq!(counts.values().sum::<usize>(), 9);
+    }
+}
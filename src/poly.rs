@@ -0,0 +1,126 @@
+use anyhow::{bail, ensure, Context, Result};
+use num::rational::Ratio;
+use num::{CheckedAdd, CheckedMul, Zero};
+
+/// A polynomial sequence sampled at `0, 1, 2, ...`, kept as the leading entry of each row of its
+/// forward difference table (Newton's form).
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Differences {
+    leading: Vec<i128>,
+}
+
+impl Differences {
+    /// Fails if `values` is not a polynomial of degree at most `max_degree`. Any `n` values fit
+    /// a polynomial of degree `n - 1`, so a lower bound needs a row of zeros in the table to
+    /// prove it.
+    pub fn new(values: &[i128], max_degree: Option<usize>) -> Result<Self> {
+        ensure!(!values.is_empty(), "no values to extrapolate from");
+        let mut row = values.to_vec();
+        let mut leading = vec![];
+        while row.iter().any(|v| !v.is_zero()) {
+            leading.push(row[0]);
+            row = row
+                .windows(2)
+                .map(|w| w[1].checked_sub(w[0]).context("overflow"))
+                .collect::<Result<_>>()?;
+        }
+        let degree = leading.len().saturating_sub(1);
+        let max = max_degree.unwrap_or(values.len() - 1);
+        if degree > max {
+            bail!(
+                "{} values are not a polynomial of degree at most {max}",
+                values.len()
+            );
+        }
+        Ok(Self { leading })
+    }
+
+    pub fn degree(&self) -> usize {
+        self.leading.len().saturating_sub(1)
+    }
+
+    /// The value at any index, before the samples or past them: `sum(C(x, k) * Δᵏf(0))`.
+    pub fn at(&self, x: i128) -> Result<i128> {
+        let mut binomial = 1i128;
+        let mut sum = 0i128;
+        for (k, &d) in self.leading.iter().enumerate() {
+            if k > 0 {
+                // C(x, k) = C(x, k - 1) * (x - k + 1) / k, and the division is exact
+                binomial = x
+                    .checked_sub(k as i128 - 1)
+                    .and_then(|f| binomial.checked_mul(f))
+                    .context("overflow")?
+                    / k as i128;
+            }
+            sum = binomial
+                .checked_mul(d)
+                .and_then(|t| sum.checked_add(t))
+                .context("overflow")?;
+        }
+        Ok(sum)
+    }
+}
+
+/// The value at `x` of the lowest degree polynomial through `points`, exactly.
+pub fn lagrange(points: &[(i128, i128)], x: i128) -> Result<Ratio<i128>> {
+    let mut sum = Ratio::zero();
+    for (i, &(xi, yi)) in points.iter().enumerate() {
+        let mut term = Ratio::from(yi);
+        for (j, &(xj, _)) in points.iter().enumerate() {
+            if i == j {
+                continue;
+            }
+            ensure!(xi != xj, "duplicate sample at {xi}");
+            let factor = Ratio::new(x.checked_sub(xj).context("overflow")?, xi - xj);
+            term = term.checked_mul(&factor).context("overflow")?;
+        }
+        sum = sum.checked_add(&term).context("overflow")?;
+    }
+    Ok(sum)
+}
+
+/// [`lagrange`], failing unless the result is an integer.
+pub fn lagrange_int(points: &[(i128, i128)], x: i128) -> Result<i128> {
+    let v = lagrange(points, x)?;
+    ensure!(v.is_integer(), "{v} is not an integer");
+    Ok(v.to_integer())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn newton() {
+        let d = Differences::new(&[10, 13, 16, 21, 30, 45], None).unwrap();
+        assert_eq!(d.degree(), 3);
+        assert_eq!(d.at(6).unwrap(), 68);
+        assert_eq!(d.at(-1).unwrap(), 5);
+        assert_eq!(d.at(2).unwrap(), 16);
+
+        // n^2 + 1
+        let d = Differences::new(&[1, 2, 5, 10], Some(2)).unwrap();
+        assert_eq!(d.at(1_000_000_000).unwrap(), 1_000_000_000_000_000_001);
+        assert_eq!(d.at(-3).unwrap(), 10);
+
+        assert!(Differences::new(&[1, 2, 4, 8], Some(2)).is_err());
+        assert_eq!(Differences::new(&[1, 2, 4, 8], None).unwrap().degree(), 3);
+        assert_eq!(Differences::new(&[0, 0], Some(0)).unwrap().degree(), 0);
+        assert!(Differences::new(&[], None).is_err());
+        assert!(Differences::new(&[0, 1, 4], Some(2))
+            .unwrap()
+            .at(i128::MAX)
+            .is_err());
+    }
+
+    #[test]
+    fn interpolate() {
+        // 2x^2 - 3x + 1 at uneven samples
+        let f = |x: i128| 2 * x * x - 3 * x + 1;
+        let points = [-4, 1, 7].map(|x| (x, f(x)));
+        assert_eq!(lagrange_int(&points, 26501365).unwrap(), f(26501365));
+        assert_eq!(lagrange(&[(0, 0), (2, 1)], 1).unwrap(), Ratio::new(1, 2));
+        assert!(lagrange_int(&[(0, 0), (2, 1)], 1).is_err());
+        assert!(lagrange(&[(1, 0), (1, 1)], 0).is_err());
+    }
+}
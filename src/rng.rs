@@ -0,0 +1,39 @@
+/// Marsaglia's xorshift64, a small deterministic generator for randomised tests. Not suitable for
+/// anything that needs good randomness.
+#[derive(Debug, Clone)]
+pub struct XorShift(u64);
+
+impl XorShift {
+    /// `seed` must not be zero, or every number is zero.
+    pub fn new(seed: u64) -> Self {
+        assert_ne!(seed, 0, "xorshift needs a nonzero seed");
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// A number in `0..n`, slightly biased towards the low end unless `n` is a power of two.
+    pub fn below(&mut self, n: u64) -> u64 {
+        self.next_u64() % n
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deterministic() {
+        let mut a = XorShift::new(0x2023_1205);
+        let mut b = a.clone();
+        let xs = (0..100).map(|_| a.below(10)).collect::<Vec<_>>();
+        assert!(xs.iter().all(|&x| x < 10));
+        assert_eq!(xs, (0..100).map(|_| b.below(10)).collect::<Vec<_>>());
+        assert_ne!(xs.iter().min(), xs.iter().max());
+    }
+}
@@ -0,0 +1,313 @@
+use std::cmp::Ordering;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::hash::Hash;
+use std::ops::Add;
+
+use num::Zero;
+
+/// Costs reached from the start states, along with enough back-links to rebuild paths.
+#[derive(Debug, Clone)]
+pub struct Distances<S, C> {
+    dist: HashMap<S, C>,
+    parent: HashMap<S, S>,
+}
+
+impl<S: Hash + Eq + Clone, C: Copy + Eq> Distances<S, C> {
+    fn new() -> Self {
+        Self {
+            dist: HashMap::new(),
+            parent: HashMap::new(),
+        }
+    }
+
+    pub fn get(&self, state: &S) -> Option<C> {
+        self.dist.get(state).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.dist.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dist.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&S, C)> {
+        self.dist.iter().map(|(s, &c)| (s, c))
+    }
+
+    /// All the states whose shortest distance is exactly `k`.
+    pub fn at(&self, k: C) -> impl Iterator<Item = &S> {
+        self.dist
+            .iter()
+            .filter(move |(_, &c)| c == k)
+            .map(|(s, _)| s)
+    }
+
+    /// The path from a start state to `to`, both ends included.
+    pub fn path(&self, to: &S) -> Option<Vec<S>> {
+        self.dist.get(to)?;
+        let mut path = vec![to.clone()];
+        while let Some(p) = self.parent.get(path.last().unwrap()) {
+            path.push(p.clone());
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    pub fn into_map(self) -> HashMap<S, C> {
+        self.dist
+    }
+}
+
+/// Breadth-first search over every state reachable from `starts`.
+pub fn bfs<S, I>(
+    starts: impl IntoIterator<Item = S>,
+    successors: impl FnMut(&S) -> I,
+) -> Distances<S, usize>
+where
+    S: Hash + Eq + Clone,
+    I: IntoIterator<Item = S>,
+{
+    bfs_within(starts, usize::MAX, successors)
+}
+
+/// Like [`bfs`], but stops expanding states `max_depth` steps away from the start.
+pub fn bfs_within<S, I>(
+    starts: impl IntoIterator<Item = S>,
+    max_depth: usize,
+    mut successors: impl FnMut(&S) -> I,
+) -> Distances<S, usize>
+where
+    S: Hash + Eq + Clone,
+    I: IntoIterator<Item = S>,
+{
+    let mut d = Distances::new();
+    let mut q = VecDeque::new();
+    for s in starts {
+        if d.dist.insert(s.clone(), 0).is_none() {
+            q.push_back((s, 0));
+        }
+    }
+    while let Some((s, depth)) = q.pop_front() {
+        if depth == max_depth {
+            continue;
+        }
+        for next in successors(&s) {
+            if let Entry::Vacant(e) = d.dist.entry(next.clone()) {
+                e.insert(depth + 1);
+                d.parent.insert(next.clone(), s.clone());
+                q.push_back((next, depth + 1));
+            }
+        }
+    }
+    d
+}
+
+/// Shortest distances where every edge costs either 0 or 1.
+pub fn bfs_0_1<S, I>(
+    starts: impl IntoIterator<Item = S>,
+    mut successors: impl FnMut(&S) -> I,
+) -> Distances<S, usize>
+where
+    S: Hash + Eq + Clone,
+    I: IntoIterator<Item = (S, usize)>,
+{
+    let mut d = Distances::new();
+    let mut q = VecDeque::new();
+    for s in starts {
+        d.dist.insert(s.clone(), 0);
+        q.push_back(s);
+    }
+    while let Some(s) = q.pop_front() {
+        let cost = d.dist[&s];
+        for (next, w) in successors(&s) {
+            assert!(w <= 1, "edge cost must be 0 or 1, got {w}");
+            let nc = cost + w;
+            if d.dist.get(&next).is_some_and(|&c| c <= nc) {
+                continue;
+            }
+            d.dist.insert(next.clone(), nc);
+            d.parent.insert(next.clone(), s.clone());
+            if w == 0 {
+                q.push_front(next);
+            } else {
+                q.push_back(next);
+            }
+        }
+    }
+    d
+}
+
+/// Dijkstra over every state reachable from `starts`.
+pub fn dijkstra<S, C, I>(
+    starts: impl IntoIterator<Item = S>,
+    successors: impl FnMut(&S) -> I,
+) -> Distances<S, C>
+where
+    S: Hash + Eq + Clone,
+    C: Copy + Ord + Zero + Add<Output = C>,
+    I: IntoIterator<Item = (S, C)>,
+{
+    search(starts, successors, |_| C::zero(), |_| false).0
+}
+
+/// Dijkstra that stops at the first state accepted by `goal`, returning its cost and path.
+pub fn dijkstra_to<S, C, I>(
+    starts: impl IntoIterator<Item = S>,
+    successors: impl FnMut(&S) -> I,
+    goal: impl FnMut(&S) -> bool,
+) -> Option<(C, Vec<S>)>
+where
+    S: Hash + Eq + Clone,
+    C: Copy + Ord + Zero + Add<Output = C>,
+    I: IntoIterator<Item = (S, C)>,
+{
+    astar(starts, successors, |_| C::zero(), goal)
+}
+
+/// A* with an admissible `heuristic`, stopping at the first state accepted by `goal`.
+pub fn astar<S, C, I>(
+    starts: impl IntoIterator<Item = S>,
+    successors: impl FnMut(&S) -> I,
+    heuristic: impl FnMut(&S) -> C,
+    goal: impl FnMut(&S) -> bool,
+) -> Option<(C, Vec<S>)>
+where
+    S: Hash + Eq + Clone,
+    C: Copy + Ord + Zero + Add<Output = C>,
+    I: IntoIterator<Item = (S, C)>,
+{
+    let (d, found) = search(starts, successors, heuristic, goal);
+    let found = found?;
+    Some((d.get(&found)?, d.path(&found)?))
+}
+
+struct Node<S, C> {
+    f: C,
+    g: C,
+    state: S,
+}
+
+impl<S, C: Ord> PartialEq for Node<S, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<S, C: Ord> Eq for Node<S, C> {}
+
+impl<S, C: Ord> PartialOrd for Node<S, C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<S, C: Ord> Ord for Node<S, C> {
+    // reversed so `BinaryHeap` pops the cheapest entry first
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.f.cmp(&self.f).then_with(|| other.g.cmp(&self.g))
+    }
+}
+
+fn search<S, C, I>(
+    starts: impl IntoIterator<Item = S>,
+    mut successors: impl FnMut(&S) -> I,
+    mut heuristic: impl FnMut(&S) -> C,
+    mut goal: impl FnMut(&S) -> bool,
+) -> (Distances<S, C>, Option<S>)
+where
+    S: Hash + Eq + Clone,
+    C: Copy + Ord + Zero + Add<Output = C>,
+    I: IntoIterator<Item = (S, C)>,
+{
+    let mut d = Distances::new();
+    let mut heap = BinaryHeap::new();
+    for s in starts {
+        d.dist.insert(s.clone(), C::zero());
+        heap.push(Node {
+            f: heuristic(&s),
+            g: C::zero(),
+            state: s,
+        });
+    }
+    while let Some(Node { g, state, .. }) = heap.pop() {
+        if d.dist.get(&state).is_some_and(|&c| c < g) {
+            continue; // stale entry
+        }
+        if goal(&state) {
+            return (d, Some(state));
+        }
+        for (next, w) in successors(&state) {
+            let ng = g + w;
+            if d.dist.get(&next).is_some_and(|&c| c <= ng) {
+                continue;
+            }
+            d.dist.insert(next.clone(), ng);
+            d.parent.insert(next.clone(), state.clone());
+            heap.push(Node {
+                f: ng + heuristic(&next),
+                g: ng,
+                state: next,
+            });
+        }
+    }
+    (d, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 0 -1-> 1 -1-> 2 -1-> 3, plus a 0 -5-> 3 shortcut that isn't one
+    fn edges(&n: &u32) -> Vec<(u32, u32)> {
+        match n {
+            0 => vec![(1, 1), (3, 5)],
+            1 => vec![(2, 1)],
+            2 => vec![(3, 1)],
+            _ => vec![],
+        }
+    }
+
+    #[test]
+    fn shortest_paths() {
+        let d = dijkstra([0], edges);
+        assert_eq!(d.get(&3), Some(3));
+        assert_eq!(d.path(&3), Some(vec![0, 1, 2, 3]));
+        assert_eq!(d.at(2).collect::<Vec<_>>(), [&2]);
+
+        assert_eq!(
+            dijkstra_to([0], edges, |&n| n == 3),
+            Some((3, vec![0, 1, 2, 3]))
+        );
+        assert_eq!(
+            astar([0], edges, |&n| 3 - n, |&n| n == 3),
+            Some((3, vec![0, 1, 2, 3]))
+        );
+        assert_eq!(dijkstra_to([0], edges, |&n| n == 4), None);
+    }
+
+    #[test]
+    fn breadth_first() {
+        let succ = |&n: &i32| [n - 1, n + 1].into_iter().filter(|n| n.abs() <= 10);
+        let d = bfs([0], succ);
+        assert_eq!(d.len(), 21);
+        assert_eq!(d.get(&-7), Some(7));
+        assert_eq!(d.path(&2), Some(vec![0, 1, 2]));
+        assert_eq!(d.at(10).count(), 2);
+
+        let d = bfs_within([0], 3, succ);
+        assert_eq!(d.len(), 7);
+
+        // doubling is free, incrementing costs 1
+        let d = bfs_0_1([1u32], |&n| {
+            [(n * 2, 0), (n + 1, 1)]
+                .into_iter()
+                .filter(|(n, _)| *n <= 64)
+        });
+        assert_eq!(d.get(&64), Some(0));
+        assert_eq!(d.get(&7), Some(2));
+        assert_eq!(d.get(&0), None);
+    }
+}
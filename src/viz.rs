@@ -0,0 +1,315 @@
+use std::collections::HashMap;
+use std::env;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, ensure, Context, Result};
+use itertools::Itertools;
+
+use crate::grid::{Grid, Pos};
+
+pub type Rgb = [u8; 3];
+
+pub const DIM: Rgb = [96, 96, 96];
+pub const WHITE: Rgb = [230, 230, 230];
+pub const RED: Rgb = [220, 50, 47];
+pub const GREEN: Rgb = [133, 153, 0];
+pub const YELLOW: Rgb = [220, 180, 0];
+pub const BLUE: Rgb = [38, 139, 210];
+pub const MAGENTA: Rgb = [211, 54, 130];
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Cell {
+    pub ch: char,
+    pub fg: Rgb,
+    pub bg: Option<Rgb>,
+}
+
+impl Cell {
+    /// Empty-looking cells (`.`) are dimmed, everything else is drawn bright.
+    pub fn plain(ch: char) -> Self {
+        let fg = if ch == '.' { DIM } else { WHITE };
+        Self { ch, fg, bg: None }
+    }
+
+    /// The single color a cell gets in an image.
+    fn pixel(&self) -> Rgb {
+        match self.bg {
+            Some(bg) => bg,
+            None if self.ch == '.' || self.ch == ' ' => [0, 0, 0],
+            None => self.fg,
+        }
+    }
+}
+
+/// How to restyle the cells of an overlay; unset parts keep the cell's own style.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct Style {
+    pub fg: Option<Rgb>,
+    pub bg: Option<Rgb>,
+    pub ch: Option<char>,
+}
+
+impl Style {
+    pub fn fg(color: Rgb) -> Self {
+        Self {
+            fg: Some(color),
+            ..Self::default()
+        }
+    }
+
+    pub fn bg(color: Rgb) -> Self {
+        Self {
+            bg: Some(color),
+            ..Self::default()
+        }
+    }
+}
+
+/// A snapshot of a grid puzzle, ready to draw.
+#[derive(Debug, Clone)]
+pub struct Frame {
+    cells: Grid<Cell>,
+}
+
+impl Frame {
+    pub fn new<T>(grid: &Grid<T>, mut cell: impl FnMut(&T) -> Cell) -> Self {
+        let cells = grid.iter().map(&mut cell).collect_vec();
+        Self {
+            cells: Grid::from_vec(cells, grid.cols()),
+        }
+    }
+
+    pub fn chars(grid: &Grid<char>) -> Self {
+        Self::new(grid, |&c| Cell::plain(c))
+    }
+
+    /// Restyles the cells at `positions`, later overlays win.
+    pub fn overlay(mut self, positions: impl IntoIterator<Item = Pos>, style: Style) -> Self {
+        for pos in positions {
+            let Some(cell) = self.cells.get_mut(pos.0, pos.1) else {
+                continue;
+            };
+            cell.fg = style.fg.unwrap_or(cell.fg);
+            cell.bg = style.bg.or(cell.bg);
+            cell.ch = style.ch.unwrap_or(cell.ch);
+        }
+        self
+    }
+
+    /// 24-bit color escapes, one line per row.
+    pub fn ansi(&self) -> String {
+        let mut out = String::new();
+        for row in self.cells.iter_rows() {
+            for cell in row {
+                let [r, g, b] = cell.fg;
+                write!(out, "\x1b[38;2;{r};{g};{b}m").unwrap();
+                if let Some([r, g, b]) = cell.bg {
+                    write!(out, "\x1b[48;2;{r};{g};{b}m").unwrap();
+                }
+                write!(out, "{}\x1b[0m", cell.ch).unwrap();
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Row-major RGB pixels, each cell a `scale`×`scale` block.
+    fn pixels(&self, scale: usize) -> Vec<Rgb> {
+        let (rows, cols) = self.cells.size();
+        let mut pixels = Vec::with_capacity(rows * cols * scale * scale);
+        for row in self.cells.iter_rows() {
+            let line = row
+                .flat_map(|c| std::iter::repeat_n(c.pixel(), scale))
+                .collect_vec();
+            for _ in 0..scale {
+                pixels.extend_from_slice(&line);
+            }
+        }
+        pixels
+    }
+
+    fn dimensions(&self, scale: usize) -> Result<(u16, u16)> {
+        let (rows, cols) = self.cells.size();
+        let w = u16::try_from(cols * scale).context("image too wide")?;
+        let h = u16::try_from(rows * scale).context("image too tall")?;
+        Ok((w, h))
+    }
+
+    pub fn write_png(&self, path: &Path, scale: usize) -> Result<()> {
+        let (w, h) = self.dimensions(scale)?;
+        let file = File::create(path).with_context(|| format!("creating {path:?}"))?;
+        let mut encoder = png::Encoder::new(BufWriter::new(file), w as u32, h as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let data = self.pixels(scale).concat();
+        encoder.write_header()?.write_image_data(&data)?;
+        Ok(())
+    }
+}
+
+/// Writes `frames` as a looping GIF, `delay` in hundredths of a second between frames.
+pub fn write_gif(path: &Path, frames: &[Frame], scale: usize, delay: u16) -> Result<()> {
+    let Some(first) = frames.first() else {
+        bail!("no frames to animate");
+    };
+    let (w, h) = first.dimensions(scale)?;
+    let mut palette: HashMap<Rgb, u8> = HashMap::new();
+    let mut indexed = vec![];
+    for frame in frames {
+        ensure!(
+            frame.dimensions(scale)? == (w, h),
+            "all frames must be the same size"
+        );
+        let mut pixels = vec![];
+        for px in frame.pixels(scale) {
+            let i = match palette.get(&px) {
+                Some(&i) => i,
+                None => {
+                    let i = u8::try_from(palette.len()).context("more than 256 colors")?;
+                    palette.insert(px, i);
+                    i
+                }
+            };
+            pixels.push(i);
+        }
+        indexed.push(pixels);
+    }
+    let mut colors = vec![[0u8; 3]; palette.len()];
+    for (c, i) in palette {
+        colors[i as usize] = c;
+    }
+    let file = File::create(path).with_context(|| format!("creating {path:?}"))?;
+    let mut encoder = gif::Encoder::new(BufWriter::new(file), w, h, &colors.concat())?;
+    encoder.set_repeat(gif::Repeat::Infinite)?;
+    for pixels in indexed {
+        let mut frame = gif::Frame::from_indexed_pixels(w, h, pixels, None);
+        frame.delay = delay;
+        encoder.write_frame(&frame)?;
+    }
+    Ok(())
+}
+
+/// Where `--viz` output goes: `--viz` draws to the terminal, `--viz=out.png` writes an image
+/// (numbered per frame when there are several) and `--viz=out.gif` an animation. Any other
+/// file extension is an error.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Output {
+    Ansi,
+    Png(PathBuf),
+    Gif(PathBuf),
+}
+
+pub const SCALE: usize = 4;
+
+impl Output {
+    pub fn from_args() -> Result<Option<Self>> {
+        for arg in env::args().skip(1) {
+            if let Some(output) = Self::parse(&arg)? {
+                return Ok(Some(output));
+            }
+        }
+        Ok(None)
+    }
+
+    fn parse(arg: &str) -> Result<Option<Self>> {
+        if arg == "--viz" {
+            return Ok(Some(Self::Ansi));
+        }
+        let Some(path) = arg.strip_prefix("--viz=") else {
+            return Ok(None);
+        };
+        let path = PathBuf::from(path);
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("png") => Ok(Some(Self::Png(path))),
+            Some("gif") => Ok(Some(Self::Gif(path))),
+            _ => bail!("--viz needs a .png or .gif file, got {path:?}"),
+        }
+    }
+
+    pub fn emit(&self, frames: &[Frame]) -> Result<()> {
+        match self {
+            Self::Ansi => {
+                for frame in frames {
+                    println!("{}", frame.ansi());
+                }
+            }
+            Self::Png(path) if frames.len() == 1 => frames[0].write_png(path, SCALE)?,
+            Self::Png(path) => {
+                let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+                for (i, frame) in frames.iter().enumerate() {
+                    frame.write_png(&path.with_file_name(format!("{stem}-{i:04}.png")), SCALE)?;
+                }
+            }
+            Self::Gif(path) => write_gif(path, frames, SCALE, 10)?,
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::grid;
+
+    #[test]
+    fn styles() {
+        let frame = Frame::chars(&grid::parse(".#\n#."))
+            .overlay([(0, 0)], Style::bg(RED))
+            .overlay([(0, 0), (5, 5)], Style::fg(GREEN));
+        assert_eq!(
+            frame.cells[(0, 0)],
+            Cell {
+                ch: '.',
+                fg: GREEN,
+                bg: Some(RED)
+            }
+        );
+        assert_eq!(frame.cells[(1, 1)], Cell::plain('.'));
+        let ansi = frame.ansi();
+        assert_eq!(ansi.lines().count(), 2);
+        assert!(ansi.starts_with("\x1b[38;2;133;153;0m\x1b[48;2;220;50;47m.\x1b[0m"));
+        assert_eq!(frame.pixels(2).len(), 16);
+        assert_eq!(frame.pixels(1), [RED, WHITE, WHITE, [0, 0, 0]]);
+    }
+
+    #[test]
+    fn args() {
+        assert_eq!(Output::parse("--viz").unwrap(), Some(Output::Ansi));
+        assert_eq!(
+            Output::parse("--viz=a.gif").unwrap(),
+            Some(Output::Gif("a.gif".into()))
+        );
+        assert_eq!(
+            Output::parse("--viz=out/a.png").unwrap(),
+            Some(Output::Png("out/a.png".into()))
+        );
+        assert_eq!(Output::parse("--vizz").unwrap(), None);
+        assert!(Output::parse("--viz=out").is_err());
+        assert!(Output::parse("--viz=out.jpg").is_err());
+        assert!(Output::parse("--viz=").is_err());
+    }
+
+    #[test]
+    fn files() {
+        let dir = env::temp_dir().join(format!("aoc-viz-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let a = Frame::chars(&grid::parse("#.\n.#"));
+        let b = a.clone().overlay([(0, 1)], Style::bg(BLUE));
+        Output::Png(dir.join("f.png"))
+            .emit(std::slice::from_ref(&a))
+            .unwrap();
+        Output::Png(dir.join("f.png"))
+            .emit(&[a.clone(), b.clone()])
+            .unwrap();
+        Output::Gif(dir.join("f.gif")).emit(&[a, b]).unwrap();
+        assert!(fs::read(dir.join("f.png")).unwrap().starts_with(b"\x89PNG"));
+        assert!(dir.join("f-0001.png").exists());
+        assert!(fs::read(dir.join("f.gif")).unwrap().starts_with(b"GIF89a"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}